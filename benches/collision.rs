@@ -0,0 +1,113 @@
+// Bullets against asteroids, everything against everything and through the spatial hash.
+// Run with `cargo bench`
+use bevy::prelude::*;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::prelude::*;
+use rusteroids::collider::Collider;
+use rusteroids::spatial::SpatialHash;
+
+// The default play field holds this many of each. Larger counts get a larger field, so things
+// are as crowded as in a busy game rather than piled on top of each other
+const FIELD_CAPACITY: u32 = 100;
+const HALF_WIDTH: f32 = 400.0;
+const HALF_HEIGHT: f32 = 300.0;
+
+struct Body {
+    entity: Entity,
+    transform: Transform,
+    collider: Collider,
+}
+
+fn scatter(rng: &mut StdRng, count: u32, first: u32, radius: f32) -> Vec<Body> {
+    let scale = (count.max(FIELD_CAPACITY) as f32 / FIELD_CAPACITY as f32).sqrt();
+    let (half_width, half_height) = (HALF_WIDTH * scale, HALF_HEIGHT * scale);
+    (0..count)
+        .map(|index| Body {
+            entity: Entity::from_raw(first + index),
+            transform: Transform::from_xyz(
+                rng.gen_range(-half_width..half_width),
+                rng.gen_range(-half_height..half_height),
+                0.0,
+            ),
+            collider: Collider::circle(radius),
+        })
+        .collect()
+}
+
+fn brute_force(bullets: &[Body], asteroids: &[Body]) -> usize {
+    let mut hits = 0;
+    for bullet in bullets {
+        for asteroid in asteroids {
+            if bullet.collider.intersects(
+                &bullet.transform,
+                &asteroid.collider,
+                &asteroid.transform,
+            ) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn spatial_hash(grid: &mut SpatialHash, bullets: &[Body], asteroids: &[Body]) -> usize {
+    // Rebuilt every time, as it is every tick
+    grid.clear();
+    for body in bullets.iter().chain(asteroids) {
+        grid.insert(
+            body.entity,
+            body.transform.translation.truncate(),
+            body.collider.radius,
+        );
+    }
+
+    let mut hits = 0;
+    let mut nearby = Vec::new();
+    for bullet in bullets {
+        grid.nearby(&bullet.transform, &bullet.collider, &mut nearby);
+        for entity in &nearby {
+            // Entities are numbered bullets first, then asteroids
+            let Some(asteroid) = (entity.index() as usize)
+                .checked_sub(bullets.len())
+                .map(|index| &asteroids[index])
+            else {
+                continue;
+            };
+            if bullet.collider.intersects(
+                &bullet.transform,
+                &asteroid.collider,
+                &asteroid.transform,
+            ) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+fn bullets_against_asteroids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bullets_against_asteroids");
+    group.sample_size(20);
+
+    for count in [100, 1000, 5000] {
+        let mut rng = StdRng::seed_from_u64(7);
+        let bullets = scatter(&mut rng, count, 0, 5.0);
+        let asteroids = scatter(&mut rng, count, count, 10.0);
+        let mut grid = SpatialHash::default();
+        assert_eq!(
+            brute_force(&bullets, &asteroids),
+            spatial_hash(&mut grid, &bullets, &asteroids)
+        );
+
+        group.bench_with_input(BenchmarkId::new("brute_force", count), &count, |b, _| {
+            b.iter(|| brute_force(&bullets, &asteroids))
+        });
+        group.bench_with_input(BenchmarkId::new("spatial_hash", count), &count, |b, _| {
+            b.iter(|| spatial_hash(&mut grid, &bullets, &asteroids))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bullets_against_asteroids);
+criterion_main!(benches);
@@ -1,7 +1,15 @@
+use crate::collider::Collider;
+use crate::mechanics::Health;
+use crate::rng::GameRng;
+use crate::settings::Setting;
+use crate::timestep::InterpolatedTransform;
+use crate::tuning::AsteroidTuning;
+use crate::{GameplaySet, PlayField};
 use bevy::prelude::*;
 use rand::prelude::*;
-use crate::GameState;
+use serde::{Deserialize, Serialize};
 
+// These constants are the defaults, the values in play come from `GameTuning`
 pub const ASTEROID_LARGE_SIZE: f32 = 80.0;
 pub const ASTEROID_MEDIUM_SIZE: f32 = 40.0;
 pub const ASTEROID_SMALL_SIZE: f32 = 20.0;
@@ -10,86 +18,269 @@ pub const ASTEROID_LARGE_SPEED: f32 = 50.0;
 pub const ASTEROID_MEDIUM_SPEED: f32 = 75.0;
 pub const ASTEROID_SMALL_SPEED: f32 = 100.0;
 
+// Speed along the shot a hit gives the fragments
+pub const ASTEROID_IMPACT_PUSH: f32 = 20.0;
+
+// Fastest an asteroid spins, in radians per second either way
+pub const ASTEROID_MAX_SPIN: f32 = 1.5;
+// How far the corners of an outline can be pulled in towards the center, as a share of the
+// radius
+pub const ASTEROID_JAGGEDNESS: f32 = 0.35;
+
 pub const INITIAL_ASTEROIDS: usize = 4;
 pub const MIN_SPAWN_DISTANCE: f32 = 100.0; // Minimum distance from center for asteroid spawn
+pub const ASTEROID_SPAWN_INTERVAL: f32 = 5.0; // Seconds between asteroids flying in from the edges
 
-#[derive(Component)]
+// Most that the direction of a fragment strays from its even share of the circle, in radians
+const FRAGMENT_JITTER: f32 = 0.35;
+
+// Corners of an asteroid outline
+const OUTLINE_CORNERS: usize = 11;
+
+// How asteroids look, picked in the settings menu. It changes what they collide by, so like
+// the dampeners it can't be changed during a run and replays keep the one they were played with
+#[derive(
+    Resource, Component, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum AsteroidTheme {
+    // A jagged outline drawn in lines, different for every asteroid, that it also collides by
+    #[default]
+    Polygons,
+    // The asteroid sprite tinted by size, colliding by a circle
+    Sprites,
+}
+
+impl Setting for AsteroidTheme {
+    const KEY: &'static str = "asteroid_theme";
+}
+
+// What new asteroids are drawn with: the theme, and the sprite for the `Sprites` theme
+#[derive(Clone, Copy)]
+pub struct AsteroidLook<'a> {
+    pub theme: AsteroidTheme,
+    pub sprite: &'a Handle<Image>,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AsteroidSize {
     Large,
     Medium,
     Small,
 }
 
+impl AsteroidSize {
+    pub fn color(&self) -> Color {
+        match self {
+            AsteroidSize::Large => Color::srgb(0.5, 0.5, 0.5),
+            AsteroidSize::Medium => Color::srgb(0.6, 0.6, 0.6),
+            AsteroidSize::Small => Color::srgb(0.7, 0.7, 0.7),
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct Asteroid;
 
 #[derive(Component)]
 pub struct AsteroidVelocity(pub Vec2);
 
+// Radians per second, counterclockwise
+#[derive(Component)]
+pub struct AsteroidSpin(pub f32);
+
+// Corners of the outline of an asteroid with the `Polygons` theme, in its own space
+#[derive(Component)]
+pub struct AsteroidOutline(pub Vec<Vec2>);
+
+// Every asteroid gets its own spin and, with the `Polygons` theme, its own outline, so
+// fragments look different from the asteroid they broke off. Nothing is drawn from the
+// generator for either when they're turned off
 pub fn spawn_asteroid(
     commands: &mut Commands,
     size: AsteroidSize,
     position: Vec3,
     velocity: Vec2,
-    asteroid_handle: &Handle<Image>,
-) {
-    let (asteroid_size, color) = match size {
-        AsteroidSize::Large => (ASTEROID_LARGE_SIZE, Color::srgb(0.5, 0.5, 0.5)),
-        AsteroidSize::Medium => (ASTEROID_MEDIUM_SIZE, Color::srgb(0.6, 0.6, 0.6)),
-        AsteroidSize::Small => (ASTEROID_SMALL_SIZE, Color::srgb(0.7, 0.7, 0.7)),
+    rng: &mut GameRng,
+    tuning: &AsteroidTuning,
+    look: AsteroidLook,
+) -> Entity {
+    let asteroid_size = tuning.size(size);
+    let spin = if tuning.max_spin > 0.0 {
+        rng.gen_range(-tuning.max_spin..=tuning.max_spin)
+    } else {
+        0.0
     };
 
-    commands.spawn((
-        Sprite {
-            image: asteroid_handle.clone(),
-            color,
-            custom_size: Some(Vec2::new(asteroid_size, asteroid_size)),
-            ..default()
-        },
+    let mut asteroid = commands.spawn((
         Transform::from_translation(position),
         GlobalTransform::default(),
+        InterpolatedTransform::default(),
         Asteroid,
         size,
         AsteroidVelocity(velocity),
+        AsteroidSpin(spin),
+        Health(tuning.hit_points),
     ));
+    match look.theme {
+        AsteroidTheme::Polygons => {
+            let outline = random_outline(rng, asteroid_size / 2.0, tuning.jaggedness);
+            asteroid.insert((Collider::star(&outline), AsteroidOutline(outline)));
+        }
+        AsteroidTheme::Sprites => {
+            asteroid.insert((
+                Sprite {
+                    image: look.sprite.clone(),
+                    color: size.color(),
+                    custom_size: Some(Vec2::new(asteroid_size, asteroid_size)),
+                    ..default()
+                },
+                Collider::circle(asteroid_size / 2.0),
+            ));
+        }
+    }
+    asteroid.id()
+}
+
+// Corners evenly spread around the center, each shifted a little along the way round and
+// pulled in by up to `jaggedness` of `radius`, which dents some of the edges inwards. Every
+// corner can still be seen from the center, as `Collider::star` needs
+fn random_outline(rng: &mut GameRng, radius: f32, jaggedness: f32) -> Vec<Vec2> {
+    let share = std::f32::consts::TAU / OUTLINE_CORNERS as f32;
+    (0..OUTLINE_CORNERS)
+        .map(|corner| {
+            let angle = share * (corner as f32 + rng.gen_range(-0.3..=0.3));
+            let distance = radius * (1.0 - rng.gen_range(0.0..=jaggedness));
+            Vec2::from_angle(angle) * distance
+        })
+        .collect()
 }
 
-pub fn spawn_initial_asteroids(
-    mut commands: Commands,
-    windows: Query<&Window>,
-    asteroid_handle: &Handle<Image>,
+// Break an asteroid of `size` moving at `velocity` into the fragments the tuning lists for it.
+// `impact` is the direction the bullet was flying. The fragments keep the asteroid's velocity
+// and fly apart, evenly spread around it but none along the line of the shot, each at the speed
+// of its size. What the spreading adds up to is taken out again, weighed by the area of each
+// fragment, so together they move on like the asteroid did, plus a push along the shot.
+// Returns the fragments
+#[allow(clippy::too_many_arguments)]
+pub fn split_asteroid(
+    commands: &mut Commands,
+    size: AsteroidSize,
+    position: Vec3,
+    velocity: Vec2,
+    impact: Vec2,
+    rng: &mut GameRng,
+    tuning: &AsteroidTuning,
+    look: AsteroidLook,
+) -> Vec<Entity> {
+    let fragments = tuning.fragments(size);
+    if fragments.is_empty() {
+        return Vec::new();
+    }
+
+    let velocities: Vec<Vec2> = if tuning.inherit_momentum {
+        let impact = impact.try_normalize().unwrap_or(Vec2::Y);
+        let share = std::f32::consts::TAU / fragments.len() as f32;
+        let spread: Vec<(f32, Vec2)> = fragments
+            .iter()
+            .enumerate()
+            .map(|(index, fragment)| {
+                let angle = impact.to_angle()
+                    + share * (index as f32 + 0.5)
+                    + rng.gen_range(-FRAGMENT_JITTER..=FRAGMENT_JITTER);
+                let mass = tuning.size(*fragment).powi(2);
+                (mass, Vec2::from_angle(angle) * tuning.speed(*fragment))
+            })
+            .collect();
+        let total_mass: f32 = spread.iter().map(|(mass, _)| mass).sum();
+        let drift = spread
+            .iter()
+            .map(|(mass, kick)| *kick * *mass)
+            .sum::<Vec2>()
+            / total_mass;
+        spread
+            .iter()
+            .map(|(_, kick)| velocity + *kick - drift + impact * tuning.impact_push)
+            .collect()
+    } else {
+        // Any direction, at the speed of the size alone
+        fragments
+            .iter()
+            .map(|fragment| {
+                let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+                let speed = tuning.speed(*fragment);
+                Vec2::new(angle.cos() * speed, angle.sin() * speed)
+            })
+            .collect()
+    };
+
+    fragments
+        .iter()
+        .zip(velocities)
+        .map(|(fragment, velocity)| {
+            spawn_asteroid(commands, *fragment, position, velocity, rng, tuning, look)
+        })
+        .collect()
+}
+
+// Spawn `count` large asteroids at random places, keeping clear of `safe_spot` where the
+// player is. `speed_scale` makes later waves faster
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_wave_asteroids(
+    commands: &mut Commands,
+    play_field: &PlayField,
+    rng: &mut GameRng,
+    tuning: &AsteroidTuning,
+    count: usize,
+    speed_scale: f32,
+    safe_spot: Vec3,
+    look: AsteroidLook,
 ) {
-    let window = windows.single().unwrap();
-    let mut rng = rand::thread_rng();
+    let half_width = play_field.half_width();
+    let half_height = play_field.half_height();
+    // Always leave some room to spawn in, however small the play field is
+    let min_spawn_distance = tuning
+        .min_spawn_distance
+        .min(half_width.min(half_height) * 0.9);
 
-    for _ in 0..INITIAL_ASTEROIDS {
+    for _ in 0..count {
         let mut position;
         loop {
-            let x = rng.gen_range(-window.width() / 2.0..window.width() / 2.0);
-            let y = rng.gen_range(-window.height() / 2.0..window.height() / 2.0);
+            let x = rng.gen_range(-half_width..half_width);
+            let y = rng.gen_range(-half_height..half_height);
             position = Vec3::new(x, y, 0.0);
 
-            // Ensure asteroid doesn't spawn too close to the center (player's initial position)
-            if position.distance(Vec3::ZERO) > MIN_SPAWN_DISTANCE {
+            // Ensure asteroid doesn't spawn too close to the player
+            if position.distance(safe_spot) > min_spawn_distance {
                 break;
             }
         }
 
         let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
-        let speed = ASTEROID_LARGE_SPEED;
+        let speed = tuning.large_speed * speed_scale;
         let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
 
-        spawn_asteroid(&mut commands, AsteroidSize::Large, position, velocity, asteroid_handle);
+        spawn_asteroid(
+            commands,
+            AsteroidSize::Large,
+            position,
+            velocity,
+            rng,
+            tuning,
+            look,
+        );
     }
 }
 
 fn move_asteroids(
-    mut asteroid_query: Query<(&mut Transform, &AsteroidVelocity), With<Asteroid>>,
+    mut asteroid_query: Query<(&mut Transform, &AsteroidVelocity, &AsteroidSpin), With<Asteroid>>,
     time: Res<Time>,
 ) {
-    for (mut transform, velocity) in asteroid_query.iter_mut() {
+    for (mut transform, velocity, spin) in asteroid_query.iter_mut() {
         transform.translation.x += velocity.0.x * time.delta_secs();
         transform.translation.y += velocity.0.y * time.delta_secs();
+        if spin.0 != 0.0 {
+            transform.rotate_z(spin.0 * time.delta_secs());
+        }
     }
 }
 
@@ -97,6 +288,152 @@ pub struct AsteroidPlugin;
 
 impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, move_asteroids.run_if(in_state(GameState::Playing)));
+        app.add_systems(FixedUpdate, move_asteroids.in_set(GameplaySet::Movement));
+    }
+}
+
+// Draws the outlines of `Polygons` asteroids. There's nothing to draw them on without a window
+pub fn asteroid_outline_plugin(app: &mut App) {
+    app.add_systems(Update, draw_asteroid_outlines);
+}
+
+fn draw_asteroid_outlines(
+    mut gizmos: Gizmos,
+    query: Query<(&Transform, &AsteroidOutline, &AsteroidSize)>,
+) {
+    for (transform, outline, size) in query.iter() {
+        let corners = outline
+            .0
+            .iter()
+            .chain(outline.0.first())
+            .map(|corner| transform.transform_point(corner.extend(0.0)).truncate());
+        gizmos.linestrip_2d(corners, size.color());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::world::CommandQueue;
+
+    // Split an asteroid in an empty world and return the size and velocity of each fragment
+    fn split(
+        size: AsteroidSize,
+        velocity: Vec2,
+        impact: Vec2,
+        tuning: &AsteroidTuning,
+        seed: u64,
+    ) -> Vec<(AsteroidSize, Vec2)> {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let sprite = Handle::default();
+        let look = AsteroidLook {
+            theme: AsteroidTheme::Polygons,
+            sprite: &sprite,
+        };
+        split_asteroid(
+            &mut commands,
+            size,
+            Vec3::new(10.0, -20.0, 0.0),
+            velocity,
+            impact,
+            &mut GameRng::new(seed),
+            tuning,
+            look,
+        );
+        queue.apply(&mut world);
+        world
+            .query::<(&AsteroidSize, &AsteroidVelocity)>()
+            .iter(&world)
+            .map(|(size, velocity)| (*size, velocity.0))
+            .collect()
+    }
+
+    // What the fragments add up to beyond the asteroid's own velocity and the push along the
+    // shot, weighed by area. Should be nothing
+    fn leftover_momentum(
+        fragments: &[(AsteroidSize, Vec2)],
+        velocity: Vec2,
+        impact: Vec2,
+        tuning: &AsteroidTuning,
+    ) -> Vec2 {
+        let push = impact.normalize() * tuning.impact_push;
+        let total_mass: f32 = fragments
+            .iter()
+            .map(|(size, _)| tuning.size(*size).powi(2))
+            .sum();
+        fragments
+            .iter()
+            .map(|(size, fragment_velocity)| {
+                (*fragment_velocity - velocity - push) * tuning.size(*size).powi(2)
+            })
+            .sum::<Vec2>()
+            / total_mass
+    }
+
+    #[test]
+    fn fragments_keep_the_momentum() {
+        let tuning = AsteroidTuning::default();
+        let velocity = Vec2::new(30.0, -40.0);
+        let impact = Vec2::new(0.0, 500.0);
+        for seed in 0..20 {
+            let fragments = split(AsteroidSize::Large, velocity, impact, &tuning, seed);
+            assert_eq!(fragments.len(), tuning.large_fragments.len());
+            let leftover = leftover_momentum(&fragments, velocity, impact, &tuning);
+            assert!(leftover.length() < 1e-3, "seed {seed}: {leftover}");
+        }
+    }
+
+    #[test]
+    fn fragments_of_mixed_sizes_keep_the_momentum() {
+        let tuning = AsteroidTuning {
+            large_fragments: vec![
+                AsteroidSize::Medium,
+                AsteroidSize::Small,
+                AsteroidSize::Small,
+            ],
+            ..default()
+        };
+        let velocity = Vec2::new(-25.0, 10.0);
+        let impact = Vec2::new(3.0, 4.0);
+        for seed in 0..20 {
+            let fragments = split(AsteroidSize::Large, velocity, impact, &tuning, seed);
+            let mut sizes: Vec<_> = fragments.iter().map(|(size, _)| *size).collect();
+            sizes.sort_by_key(|size| *size as u8);
+            assert_eq!(sizes, tuning.large_fragments);
+            let leftover = leftover_momentum(&fragments, velocity, impact, &tuning);
+            assert!(leftover.length() < 1e-3, "seed {seed}: {leftover}");
+        }
+    }
+
+    #[test]
+    fn fragments_fly_apart() {
+        let tuning = AsteroidTuning::default();
+        let fragments = split(AsteroidSize::Medium, Vec2::ZERO, Vec2::X, &tuning, 7);
+        assert!(fragments.len() >= 2);
+        for (index, (_, velocity)) in fragments.iter().enumerate() {
+            for (_, other_velocity) in &fragments[index + 1..] {
+                assert!(velocity.distance(*other_velocity) > 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn smallest_asteroids_leave_nothing() {
+        let tuning = AsteroidTuning::default();
+        assert!(split(AsteroidSize::Small, Vec2::ONE, Vec2::X, &tuning, 7).is_empty());
+    }
+
+    #[test]
+    fn without_momentum_fragments_move_at_the_speed_of_their_size() {
+        let tuning = AsteroidTuning {
+            inherit_momentum: false,
+            ..default()
+        };
+        let velocity = Vec2::new(500.0, 0.0);
+        for (size, fragment_velocity) in split(AsteroidSize::Large, velocity, Vec2::X, &tuning, 7) {
+            assert!((fragment_velocity.length() - tuning.speed(size)).abs() < 1e-3);
+        }
     }
 }
@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use clap::{CommandFactory, Parser, ValueEnum, error::ErrorKind};
+use rusteroids::replay::Replay;
+use rusteroids::{GameState, RusteroidsPlugin};
+
+/// Modern remake of the old-school asteroids
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Play every run with this seed instead of a random one
+    #[arg(long, conflicts_with = "replay")]
+    seed: Option<u64>,
+
+    /// Start with godmode on, the ship can't be destroyed. Not saved to the settings
+    #[arg(long, conflicts_with = "replay")]
+    godmode: bool,
+
+    /// Sound volume, from 0 to 9. Not saved to the settings
+    #[arg(long, value_parser = clap::value_parser!(u32).range(0..=9))]
+    volume: Option<u32>,
+
+    /// Window size as WIDTHxHEIGHT, which is also the size of the play field
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_window_size)]
+    window: Option<(f32, f32)>,
+
+    /// Start in borderless fullscreen on the current monitor
+    #[arg(long)]
+    fullscreen: bool,
+
+    /// Skip the splash screen and start in the main menu, or straight in the game
+    #[arg(long, value_name = "TO", num_args = 0..=1, default_missing_value = "menu")]
+    skip_splash: Option<SkipSplash>,
+
+    /// Run the simulation without a window or renderer, e.g. for CI, bots and balance runs
+    #[arg(long)]
+    headless: bool,
+
+    /// Quit after this many frames
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    max_frames: Option<u32>,
+
+    /// Folder to load the assets from
+    #[arg(long, value_name = "DIR")]
+    asset_dir: Option<String>,
+
+    /// Simulation ticks per second
+    #[arg(long, value_name = "HZ", value_parser = parse_tick_rate, conflicts_with = "replay")]
+    tick_rate: Option<f64>,
+
+    /// Watch a recorded run instead of playing
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Record every run to this folder, also when headless
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    replay_dir: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum SkipSplash {
+    /// Start in the main menu
+    Menu,
+    /// Start a game right away
+    Game,
+}
+
+fn parse_window_size(value: &str) -> Result<(f32, f32), String> {
+    let invalid = || format!("expected WIDTHxHEIGHT, e.g. 1280x720, got {value:?}");
+    let (width, height) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: u32 = width.trim().parse().map_err(|_| invalid())?;
+    let height: u32 = height.trim().parse().map_err(|_| invalid())?;
+    if !(1..=16384).contains(&width) || !(1..=16384).contains(&height) {
+        return Err(format!(
+            "width and height must be between 1 and 16384, got {value:?}"
+        ));
+    }
+    Ok((width as f32, height as f32))
+}
+
+fn parse_tick_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(tick_rate) if tick_rate > 0.0 && tick_rate <= 1000.0 => Ok(tick_rate),
+        _ => Err(format!(
+            "expected a number of ticks per second between 0 and 1000, got {value:?}"
+        )),
+    }
+}
+
+impl Cli {
+    // Configure the game from the options. Exits with an error message when a file given on
+    // the command line can't be loaded
+    pub fn into_plugin(self) -> RusteroidsPlugin {
+        // The cargo feature builds a binary that is always headless
+        let headless = self.headless || cfg!(feature = "headless");
+
+        let mut plugin = RusteroidsPlugin::new()
+            .headless(headless)
+            .fullscreen(self.fullscreen)
+            .with_seed(self.seed)
+            .with_max_frames(self.max_frames);
+        if self.godmode {
+            plugin = plugin.with_godmode(true);
+        }
+        if let Some(volume) = self.volume {
+            plugin = plugin.with_volume(volume);
+        }
+        if let Some((width, height)) = self.window {
+            plugin = plugin.with_window_size(width, height);
+        }
+        if let Some(asset_dir) = self.asset_dir {
+            plugin = plugin.with_asset_root(asset_dir);
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            plugin = plugin.with_tick_rate(tick_rate);
+        }
+        if let Some(replay_dir) = self.replay_dir {
+            plugin = plugin.with_replay_dir(replay_dir);
+        }
+        match self.skip_splash {
+            Some(SkipSplash::Menu) => plugin = plugin.skip_splash(),
+            Some(SkipSplash::Game) => plugin = plugin.with_starting_state(GameState::Loading),
+            None => {}
+        }
+
+        if let Some(path) = self.replay {
+            match Replay::load(&path) {
+                Ok(replay) => plugin = plugin.with_replay(replay),
+                Err(error) => Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("could not load replay {}: {error}", path.display()),
+                    )
+                    .exit(),
+            }
+        }
+
+        plugin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("rusteroids").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn window_size() {
+        assert_eq!(parse_window_size("1280x720"), Ok((1280.0, 720.0)));
+        assert_eq!(parse_window_size("800X600"), Ok((800.0, 600.0)));
+        assert_eq!(parse_window_size(" 640 x 480 "), Ok((640.0, 480.0)));
+        assert_eq!(parse_window_size("16384x1"), Ok((16384.0, 1.0)));
+    }
+
+    #[test]
+    fn window_size_out_of_range() {
+        assert!(parse_window_size("0x720").is_err());
+        assert!(parse_window_size("1280x0").is_err());
+        assert!(parse_window_size("16385x720").is_err());
+    }
+
+    #[test]
+    fn window_size_garbage() {
+        for value in [
+            "",
+            "1280",
+            "1280x",
+            "x720",
+            "1280*720",
+            "widexhigh",
+            "-1x720",
+            "1.5x2",
+        ] {
+            assert!(parse_window_size(value).is_err(), "{value:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn tick_rate() {
+        assert_eq!(parse_tick_rate("60"), Ok(60.0));
+        assert_eq!(parse_tick_rate("0.5"), Ok(0.5));
+        assert_eq!(parse_tick_rate("1000"), Ok(1000.0));
+        for value in ["0", "-60", "1000.1", "fast", "NaN", "inf", ""] {
+            assert!(parse_tick_rate(value).is_err(), "{value:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn volume_range() {
+        assert_eq!(parse(&["--volume", "0"]).unwrap().volume, Some(0));
+        assert_eq!(parse(&["--volume", "9"]).unwrap().volume, Some(9));
+        assert!(parse(&["--volume", "10"]).is_err());
+        assert!(parse(&["--volume", "-1"]).is_err());
+        assert!(parse(&["--volume", "loud"]).is_err());
+    }
+
+    #[test]
+    fn options_go_through_their_parsers() {
+        let cli = parse(&["--window", "1024x768", "--tick-rate", "120"]).unwrap();
+        assert_eq!(cli.window, Some((1024.0, 768.0)));
+        assert_eq!(cli.tick_rate, Some(120.0));
+        assert!(parse(&["--window", "big"]).is_err());
+        assert!(parse(&["--tick-rate", "0"]).is_err());
+    }
+
+    #[test]
+    fn replay_conflicts_with_run_options() {
+        assert!(parse(&["--replay", "run.rsrp", "--seed", "7"]).is_err());
+        assert!(parse(&["--replay", "run.rsrp", "--replay-dir", "replays"]).is_err());
+    }
+
+    #[test]
+    fn command_is_well_formed() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+
+// The shape something collides with, attached when it spawns. It's given in the entity's own
+// space, so it moves, turns and scales with the entity. The circle alone decides a hit, unless
+// there's an outline: then the circle only rules out things too far away to touch and the
+// outline decides
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Collider {
+    pub radius: f32,
+    // The outline as convex pieces, each with its corners in order around it. Empty for a
+    // circle
+    pub pieces: Vec<Vec<Vec2>>,
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            radius,
+            pieces: Vec::new(),
+        }
+    }
+
+    // A convex outline, with the smallest circle around the center that holds it
+    pub fn convex(outline: Vec<Vec2>) -> Self {
+        Self {
+            radius: enclosing_radius(&outline),
+            pieces: vec![outline],
+        }
+    }
+
+    // An outline that may be concave, as long as every corner can be seen from the center. It's
+    // cut into a triangle per edge, fanned out from the center
+    pub fn star(outline: &[Vec2]) -> Self {
+        Self {
+            radius: enclosing_radius(outline),
+            pieces: edges(outline)
+                .map(|(start, end)| vec![Vec2::ZERO, start, end])
+                .collect(),
+        }
+    }
+
+    pub fn rectangle(size: Vec2) -> Self {
+        let half = size / 2.0;
+        Self::convex(vec![
+            Vec2::new(-half.x, -half.y),
+            Vec2::new(half.x, -half.y),
+            Vec2::new(half.x, half.y),
+            Vec2::new(-half.x, half.y),
+        ])
+    }
+
+    // Whether this collider at `transform` touches `other` at `other_transform`
+    pub fn intersects(
+        &self,
+        transform: &Transform,
+        other: &Collider,
+        other_transform: &Transform,
+    ) -> bool {
+        let radius = self.radius * transform.scale.x;
+        let other_radius = other.radius * other_transform.scale.x;
+        let distance = transform.translation.distance(other_transform.translation);
+        if distance >= radius + other_radius {
+            return false;
+        }
+
+        let center = transform.translation.truncate();
+        let other_center = other_transform.translation.truncate();
+        let pieces = self.world_pieces(transform);
+        let other_pieces = other.world_pieces(other_transform);
+        match (pieces.is_empty(), other_pieces.is_empty()) {
+            (true, true) => true,
+            (false, true) => pieces
+                .iter()
+                .any(|piece| outline_touches_circle(piece, other_center, other_radius)),
+            (true, false) => other_pieces
+                .iter()
+                .any(|piece| outline_touches_circle(piece, center, radius)),
+            (false, false) => pieces.iter().any(|piece| {
+                other_pieces
+                    .iter()
+                    .any(|other_piece| outlines_overlap(piece, other_piece))
+            }),
+        }
+    }
+
+    fn world_pieces(&self, transform: &Transform) -> Vec<Vec<Vec2>> {
+        self.pieces
+            .iter()
+            .map(|piece| {
+                piece
+                    .iter()
+                    .map(|corner| transform.transform_point(corner.extend(0.0)).truncate())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Of the smallest circle around the center that holds all of `outline`
+fn enclosing_radius(outline: &[Vec2]) -> f32 {
+    outline
+        .iter()
+        .map(|corner| corner.length())
+        .fold(0.0, f32::max)
+}
+
+// Pairs of neighbouring corners, closing the outline
+fn edges(outline: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+}
+
+fn outline_touches_circle(outline: &[Vec2], center: Vec2, radius: f32) -> bool {
+    // Inside when the center is on the same side of every edge, whichever way the corners go
+    let sides = edges(outline).map(|(start, end)| (end - start).perp_dot(center - start));
+    let (mut left, mut right) = (false, false);
+    for side in sides {
+        left |= side > 0.0;
+        right |= side < 0.0;
+    }
+    if !(left && right) {
+        return true;
+    }
+
+    edges(outline).any(|(start, end)| {
+        let edge = end - start;
+        let along = ((center - start).dot(edge) / edge.length_squared()).clamp(0.0, 1.0);
+        center.distance(start + edge * along) < radius
+    })
+}
+
+// Separating axis test: two convex outlines overlap unless one of their edges separates them
+fn outlines_overlap(outline: &[Vec2], other_outline: &[Vec2]) -> bool {
+    let axes = edges(outline)
+        .chain(edges(other_outline))
+        .map(|(start, end)| (end - start).perp());
+
+    for axis in axes {
+        let (min, max) = project(outline, axis);
+        let (other_min, other_max) = project(other_outline, axis);
+        if max <= other_min || other_max <= min {
+            return false;
+        }
+    }
+    true
+}
+
+fn project(outline: &[Vec2], axis: Vec2) -> (f32, f32) {
+    outline
+        .iter()
+        .map(|corner| corner.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Transform {
+        Transform::from_xyz(x, y, 0.0)
+    }
+
+    #[test]
+    fn circles_touch_when_closer_than_their_radii() {
+        let circle = Collider::circle(10.0);
+        assert!(circle.intersects(&at(0.0, 0.0), &circle, &at(19.0, 0.0)));
+        assert!(!circle.intersects(&at(0.0, 0.0), &circle, &at(20.0, 0.0)));
+    }
+
+    #[test]
+    fn separating_axis_rules_out_corners_that_only_share_the_circles() {
+        let square = Collider::rectangle(Vec2::splat(20.0));
+        // The enclosing circles overlap along the diagonal, the squares don't
+        assert!(!square.intersects(&at(0.0, 0.0), &square, &at(21.0, 21.0)));
+        assert!(square.intersects(&at(0.0, 0.0), &square, &at(19.0, 19.0)));
+    }
+
+    #[test]
+    fn separating_axis_finds_a_turned_outline() {
+        let square = Collider::rectangle(Vec2::splat(20.0));
+        let turned =
+            at(24.0, 0.0).with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+        // Turned by 45°, the corner reaches about 14.1 towards the other square
+        assert!(square.intersects(&at(0.0, 0.0), &square, &turned));
+        assert!(!square.intersects(&at(0.0, 0.0), &square, &at(24.0, 0.0)));
+    }
+
+    #[test]
+    fn outline_and_circle() {
+        let square = Collider::rectangle(Vec2::splat(20.0));
+        let circle = Collider::circle(2.0);
+        assert!(square.intersects(&at(0.0, 0.0), &circle, &at(11.0, 0.0)));
+        assert!(!square.intersects(&at(0.0, 0.0), &circle, &at(11.5, 11.5)));
+        // Entirely inside
+        assert!(circle.intersects(&at(0.0, 0.0), &square, &at(0.0, 0.0)));
+    }
+
+    // A four-pointed star, with its points on the axes and deep notches between them
+    fn star() -> Collider {
+        Collider::star(&[
+            Vec2::new(20.0, 0.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(0.0, 20.0),
+            Vec2::new(-3.0, 3.0),
+            Vec2::new(-20.0, 0.0),
+            Vec2::new(-3.0, -3.0),
+            Vec2::new(0.0, -20.0),
+            Vec2::new(3.0, -3.0),
+        ])
+    }
+
+    #[test]
+    fn star_is_cut_into_a_triangle_per_edge() {
+        let star = star();
+        assert_eq!(star.radius, 20.0);
+        assert_eq!(star.pieces.len(), 8);
+        assert!(star.pieces.iter().all(|piece| piece[0] == Vec2::ZERO));
+    }
+
+    #[test]
+    fn star_notches_are_empty() {
+        let star = star();
+        let pebble = Collider::circle(1.0);
+        // In the notch between two points, inside the enclosing circle
+        assert!(!star.intersects(&at(0.0, 0.0), &pebble, &at(10.0, 10.0)));
+        // On a point
+        assert!(star.intersects(&at(0.0, 0.0), &pebble, &at(15.0, 0.0)));
+    }
+
+    #[test]
+    fn star_pieces_against_an_outline() {
+        let star = star();
+        let square = Collider::rectangle(Vec2::splat(4.0));
+        assert!(!star.intersects(&at(0.0, 0.0), &square, &at(10.0, 10.0)));
+        assert!(star.intersects(&at(0.0, 0.0), &square, &at(0.0, 16.0)));
+    }
+
+    #[test]
+    fn scale_grows_the_outline() {
+        let square = Collider::rectangle(Vec2::splat(20.0));
+        let pebble = Collider::circle(1.0);
+        let scaled = at(0.0, 0.0).with_scale(Vec3::splat(2.0));
+        assert!(square.intersects(&scaled, &pebble, &at(19.5, 0.0)));
+        assert!(!square.intersects(&at(0.0, 0.0), &pebble, &at(19.5, 0.0)));
+    }
+}
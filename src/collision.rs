@@ -0,0 +1,166 @@
+use bevy::ecs::system::SystemParam;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+
+use crate::collider::Collider;
+use crate::edges::{EdgeKind, EdgePolicy};
+use crate::spatial::SpatialHash;
+use crate::tuning::GameTuning;
+use crate::{GameplaySet, PlayField, mechanics};
+
+// Collisions are found and dealt with in two steps. Detection systems look for overlaps and
+// send one of these events for each hit, then the systems after them read the events and
+// apply the damage, score and so on. Every entity takes part in at most one hit per tick, so
+// a bullet can't take down two asteroids at once and an asteroid isn't split twice by two
+// bullets
+
+// A bullet, the ship's or a saucer's, hit an asteroid
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BulletHitAsteroid {
+    pub bullet: Entity,
+    pub asteroid: Entity,
+}
+
+// The ship ran into an asteroid
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerHitAsteroid {
+    pub player: Entity,
+    pub asteroid: Entity,
+}
+
+// One of the ship's bullets hit a saucer
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BulletHitEnemy {
+    pub bullet: Entity,
+    pub enemy: Entity,
+}
+
+// The ship ran into a saucer or one of its bullets
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerHitEnemy {
+    pub player: Entity,
+    pub enemy: Entity,
+}
+
+// The ship ran into a power-up
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerHitPowerUp {
+    pub player: Entity,
+    pub power_up: Entity,
+}
+
+// Entities that already took part in a hit this tick
+#[derive(Resource, Debug, Default)]
+pub struct Consumed(HashSet<Entity>);
+
+// What the detection systems look for hits with. The play field wraps around for things with
+// the `Wrap` edge policy, so these find hits across the edges too: something poking out on
+// the right touches what's at the left edge
+#[derive(SystemParam)]
+pub struct Contacts<'w> {
+    spatial_hash: Res<'w, SpatialHash>,
+    consumed: ResMut<'w, Consumed>,
+    play_field: Res<'w, PlayField>,
+    tuning: Res<'w, GameTuning>,
+}
+
+impl Contacts<'_> {
+    pub fn is_consumed(&self, entity: Entity) -> bool {
+        self.consumed.0.contains(&entity)
+    }
+
+    // Take them out of the hits for the rest of the tick
+    pub fn consume(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.consumed.0.extend(entities);
+    }
+
+    // Things of `kind` that may touch `collider` at `transform` and haven't been in a hit this
+    // tick, in a fixed order
+    pub fn nearby(
+        &self,
+        transform: &Transform,
+        collider: &Collider,
+        kind: EdgeKind,
+        found: &mut Vec<Entity>,
+    ) {
+        let center = transform.translation.truncate();
+        let radius = collider.radius * transform.scale.x;
+        if self.wraps(kind) {
+            // Also look where the copies of the circle on the other side of the edges are, if
+            // anything can reach that far
+            let reach = radius + self.spatial_hash.max_radius();
+            let half_extents =
+                Vec2::new(self.play_field.half_width(), self.play_field.half_height());
+            let size = half_extents * 2.0;
+            let laps = |axis: usize| {
+                let mut laps = vec![0.0];
+                if center[axis] + reach > half_extents[axis] {
+                    laps.push(-1.0);
+                }
+                if center[axis] - reach < -half_extents[axis] {
+                    laps.push(1.0);
+                }
+                laps
+            };
+            let mut centers = Vec::new();
+            for x in laps(0) {
+                for y in laps(1) {
+                    centers.push(center + Vec2::new(x, y) * size);
+                }
+            }
+            self.spatial_hash.query_around(&centers, radius, found);
+        } else {
+            self.spatial_hash.query(center, radius, found);
+        }
+        found.retain(|entity| !self.is_consumed(*entity));
+    }
+
+    // Whether `collider` at `transform` touches a thing of `kind`, measured the short way
+    // around the play field if it wraps
+    pub fn touching(
+        &self,
+        transform: &Transform,
+        collider: &Collider,
+        other_transform: &Transform,
+        other_collider: &Collider,
+        kind: EdgeKind,
+    ) -> bool {
+        let mut other_transform = *other_transform;
+        if self.wraps(kind) {
+            let size = Vec2::new(self.play_field.width, self.play_field.height);
+            let offset = other_transform.translation.truncate() - transform.translation.truncate();
+            let laps = (offset / size).round();
+            if laps != Vec2::ZERO {
+                other_transform.translation -= (laps * size).extend(0.0);
+            }
+        }
+        collider.intersects(transform, other_collider, &other_transform)
+    }
+
+    fn wraps(&self, kind: EdgeKind) -> bool {
+        self.tuning.collision.across_edges && self.tuning.edge_policy(kind) == EdgePolicy::Wrap
+    }
+}
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Consumed>()
+            .add_event::<BulletHitAsteroid>()
+            .add_event::<PlayerHitAsteroid>()
+            .add_event::<BulletHitEnemy>()
+            .add_event::<PlayerHitEnemy>()
+            .add_event::<PlayerHitPowerUp>()
+            .add_systems(
+                FixedUpdate,
+                clear_consumed
+                    .in_set(GameplaySet::Collision)
+                    .before(mechanics::detect_bullet_asteroid_hits),
+            );
+    }
+}
+
+fn clear_consumed(mut consumed: ResMut<Consumed>) {
+    consumed.0.clear();
+}
@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asteroid::{Asteroid, AsteroidVelocity};
+use crate::collider::Collider;
+use crate::mechanics::{Bullet, BulletVelocity, HitEntities};
+use crate::player::{Player, PlayerVelocity, ShipDestroyed, ShipLoss};
+use crate::powerup::PowerUp;
+use crate::saucer::{Saucer, SaucerBullet, SaucerVelocity};
+use crate::scoring::Combo;
+use crate::tuning::GameTuning;
+use crate::{GameplaySet, Godmode, PlayField};
+
+// What happens to something that reaches the edge of the play field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgePolicy {
+    // Comes back in at the opposite edge
+    Wrap,
+    // Turns back into the play field as if it hit a wall
+    Bounce,
+    // Is gone. For the ship that costs a life
+    Despawn,
+    // Stops at the edge
+    Clamp,
+}
+
+// The kinds of things in play, each with its own edge policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Ship,
+    Asteroid,
+    Bullet,
+    Saucer,
+    PowerUp,
+}
+
+// Sets the edge policies of a run. The `edges` section of the gameplay tuning can change the
+// policy of any kind on top of the mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    // Everything wraps around the screen, except bullets, which are gone at the edge
+    #[default]
+    Classic,
+    // Bullets wrap around the screen as well, like in the arcade original
+    Arcade,
+    // Walled in: everything bounces off the edges and bullets are gone at them
+    Arena,
+}
+
+impl GameMode {
+    pub fn edge_policy(&self, kind: EdgeKind) -> EdgePolicy {
+        match (self, kind) {
+            (GameMode::Classic, EdgeKind::Bullet) => EdgePolicy::Despawn,
+            (GameMode::Classic | GameMode::Arcade, _) => EdgePolicy::Wrap,
+            (GameMode::Arena, EdgeKind::Bullet) => EdgePolicy::Despawn,
+            (GameMode::Arena, _) => EdgePolicy::Bounce,
+        }
+    }
+}
+
+pub struct EdgesPlugin;
+
+impl Plugin for EdgesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                ship_at_edges,
+                asteroids_at_edges,
+                bullets_at_edges,
+                saucers_at_edges,
+                power_ups_at_edges,
+            )
+                .chain()
+                .in_set(GameplaySet::Bounds),
+        );
+    }
+}
+
+// Applies `policy` to something at `translation` moving at `velocity`. Wrapping and despawning
+// go by its center, bouncing and clamping by its sides, `half_size` away from the center.
+// Returns false when it's gone
+fn keep_in_play_field(
+    policy: EdgePolicy,
+    translation: &mut Vec3,
+    velocity: &mut Vec2,
+    half_size: Vec2,
+    play_field: &PlayField,
+) -> bool {
+    let half_extents = Vec2::new(play_field.half_width(), play_field.half_height());
+
+    for axis in 0..2 {
+        let half_extent = half_extents[axis];
+        let limit = (half_extent - half_size[axis]).max(0.0);
+        let position = translation[axis];
+
+        match policy {
+            EdgePolicy::Wrap => {
+                if position > half_extent {
+                    translation[axis] = -half_extent;
+                } else if position < -half_extent {
+                    translation[axis] = half_extent;
+                }
+            }
+            EdgePolicy::Despawn => {
+                if position.abs() > half_extent {
+                    return false;
+                }
+            }
+            EdgePolicy::Bounce => {
+                if position > limit {
+                    translation[axis] = limit;
+                    velocity[axis] = -velocity[axis].abs();
+                } else if position < -limit {
+                    translation[axis] = -limit;
+                    velocity[axis] = velocity[axis].abs();
+                }
+            }
+            EdgePolicy::Clamp => {
+                if position > limit {
+                    translation[axis] = limit;
+                    velocity[axis] = velocity[axis].min(0.0);
+                } else if position < -limit {
+                    translation[axis] = -limit;
+                    velocity[axis] = velocity[axis].max(0.0);
+                }
+            }
+        }
+    }
+    true
+}
+
+fn half_size(sprite: &Sprite) -> Vec2 {
+    sprite.custom_size.unwrap_or_default() / 2.0
+}
+
+fn ship_at_edges(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut PlayerVelocity, &Sprite), With<Player>>,
+    godmode: Res<Godmode>,
+    play_field: Res<PlayField>,
+    tuning: Res<GameTuning>,
+) {
+    let policy = match tuning.edge_policy(EdgeKind::Ship) {
+        // Nothing destroys the ship in godmode, so it stops at the edge instead
+        EdgePolicy::Despawn if godmode.0 => EdgePolicy::Clamp,
+        policy => policy,
+    };
+
+    for (entity, mut transform, mut velocity, sprite) in query.iter_mut() {
+        let half_size = half_size(sprite);
+        if !keep_in_play_field(
+            policy,
+            &mut transform.translation,
+            &mut velocity.0,
+            half_size,
+            &play_field,
+        ) {
+            commands.trigger_targets(ShipDestroyed(ShipLoss::Edge), entity);
+        }
+    }
+}
+
+fn asteroids_at_edges(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut AsteroidVelocity, &Collider), With<Asteroid>>,
+    play_field: Res<PlayField>,
+    tuning: Res<GameTuning>,
+) {
+    let policy = tuning.edge_policy(EdgeKind::Asteroid);
+
+    for (entity, mut transform, mut velocity, collider) in query.iter_mut() {
+        // Asteroids spin, so they're kept in by the circle around them
+        let half_size = Vec2::splat(collider.radius * transform.scale.x);
+        if !keep_in_play_field(
+            policy,
+            &mut transform.translation,
+            &mut velocity.0,
+            half_size,
+            &play_field,
+        ) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn bullets_at_edges(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut BulletVelocity,
+            &Sprite,
+            Has<SaucerBullet>,
+            Has<HitEntities>,
+        ),
+        With<Bullet>,
+    >,
+    mut combo: ResMut<Combo>,
+    play_field: Res<PlayField>,
+    tuning: Res<GameTuning>,
+) {
+    let policy = tuning.edge_policy(EdgeKind::Bullet);
+
+    for (entity, mut transform, mut velocity, sprite, from_saucer, hit) in query.iter_mut() {
+        let half_size = half_size(sprite);
+        if !keep_in_play_field(
+            policy,
+            &mut transform.translation,
+            &mut velocity.0,
+            half_size,
+            &play_field,
+        ) {
+            // The shot missed
+            if !from_saucer && !hit {
+                combo.break_streak();
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn saucers_at_edges(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &Saucer,
+        &mut Transform,
+        &mut SaucerVelocity,
+        &Sprite,
+    )>,
+    play_field: Res<PlayField>,
+    tuning: Res<GameTuning>,
+) {
+    let policy = tuning.edge_policy(EdgeKind::Saucer);
+
+    for (entity, saucer, mut transform, mut velocity, sprite) in query.iter_mut() {
+        // A saucer that's leaving flies off through any edge
+        if saucer.is_leaving() {
+            continue;
+        }
+        let half_size = half_size(sprite);
+        if !keep_in_play_field(
+            policy,
+            &mut transform.translation,
+            &mut velocity.0,
+            half_size,
+            &play_field,
+        ) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn power_ups_at_edges(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut PowerUp, &Sprite)>,
+    play_field: Res<PlayField>,
+    tuning: Res<GameTuning>,
+) {
+    let policy = tuning.edge_policy(EdgeKind::PowerUp);
+
+    for (entity, mut transform, mut power_up, sprite) in query.iter_mut() {
+        let half_size = half_size(sprite);
+        if !keep_in_play_field(
+            policy,
+            &mut transform.translation,
+            &mut power_up.velocity,
+            half_size,
+            &play_field,
+        ) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::events::{AsteroidDestroyed, PlayerDied, SaucerDestroyed};
+use crate::{GameState, OnGameScreen, player};
+
+// Pieces flying apart where something was destroyed
+const DEBRIS_PIECES: usize = 8;
+const DEBRIS_SPEED: f32 = 120.0;
+const DEBRIS_SIZE: f32 = 3.0;
+// Seconds until a piece has faded out
+const DEBRIS_LIFETIME: f32 = 0.6;
+
+// A piece of debris. Only for show, so it moves every frame and takes nothing from the game's
+// generator, which keeps replays the same with or without it
+#[derive(Component)]
+struct Debris {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+// Debris for destroyed asteroids, saucers and ships. Added with a window only
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(asteroid_debris)
+            .add_observer(saucer_debris)
+            .add_observer(ship_debris)
+            .add_systems(Update, move_debris.run_if(not(in_state(GameState::Paused))));
+    }
+}
+
+// `pieces` flying out from `position`, evenly spread
+fn burst(commands: &mut Commands, position: Vec3, color: Color, pieces: usize) {
+    for piece in 0..pieces {
+        let direction = Vec2::from_angle(std::f32::consts::TAU * piece as f32 / pieces as f32);
+        commands.spawn((
+            Sprite {
+                color,
+                custom_size: Some(Vec2::splat(DEBRIS_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(position),
+            Debris {
+                velocity: direction * DEBRIS_SPEED,
+                lifetime: Timer::from_seconds(DEBRIS_LIFETIME, TimerMode::Once),
+            },
+            OnGameScreen,
+        ));
+    }
+}
+
+fn asteroid_debris(trigger: Trigger<AsteroidDestroyed>, mut commands: Commands) {
+    let event = trigger.event();
+    burst(
+        &mut commands,
+        event.position,
+        event.size.color(),
+        DEBRIS_PIECES,
+    );
+}
+
+fn saucer_debris(trigger: Trigger<SaucerDestroyed>, mut commands: Commands) {
+    let event = trigger.event();
+    burst(
+        &mut commands,
+        event.position,
+        event.size.color(),
+        DEBRIS_PIECES,
+    );
+}
+
+fn ship_debris(trigger: Trigger<PlayerDied>, mut commands: Commands) {
+    burst(
+        &mut commands,
+        trigger.event().position,
+        player::PLAYER_COLOR,
+        DEBRIS_PIECES * 2,
+    );
+}
+
+fn move_debris(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Debris, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut debris, mut transform, mut sprite) in query.iter_mut() {
+        if debris.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (debris.velocity * time.delta_secs()).extend(0.0);
+        sprite.color.set_alpha(debris.lifetime.fraction_remaining());
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::asteroid::AsteroidSize;
+use crate::player::ShipLoss;
+use crate::saucer::SaucerSize;
+
+// The main moments of a run, triggered as they happen. Scoring, stats, effects and the wave
+// banner are observers of these rather than part of the gameplay systems, and other plugins
+// can listen the same way, e.g. for sound once the game has some.
+//
+// They are only triggered for observers, never written to an `Events` queue, so read them with
+// an observer rather than an `EventReader`, which would fail for lack of the resource:
+//
+// app.add_observer(|trigger: Trigger<AsteroidDestroyed>| info!("{:?}", trigger.event()));
+//
+// They are triggered from the fixed timestep, so an observer that changes gameplay state stays
+// deterministic in replays, as long as it doesn't draw from the game's generator
+
+// Who fired a shot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shooter {
+    Player,
+    Saucer,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AsteroidDestroyed {
+    pub size: AsteroidSize,
+    pub position: Vec3,
+    pub by: Shooter,
+}
+
+// Shot down or rammed by the ship
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SaucerDestroyed {
+    pub size: SaucerSize,
+    pub position: Vec3,
+}
+
+// One pull of the trigger, however many bullets it fires
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ShotFired {
+    pub position: Vec3,
+    pub direction: Vec2,
+    pub by: Shooter,
+}
+
+// The ship was lost, `lives_left` is 0 when that ends the run
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDied {
+    pub loss: ShipLoss,
+    pub position: Vec3,
+    pub lives_left: u32,
+}
+
+// A ship came into play, at the start of a run or after a lost life
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerSpawned {
+    pub position: Vec3,
+}
+
+// The last asteroid of wave `number` is gone
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WaveCleared {
+    pub number: u32,
+    pub bonus: u32,
+}
+
+// The last life is lost, right before the game switches to `GameState::GameOver`
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GameOverReached {
+    pub loss: ShipLoss,
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    app::PluginGroupBuilder, input::InputPlugin, log::LogPlugin, prelude::*,
+    state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+
+use crate::rng::GameRng;
+use crate::tuning::{self, TuningHandle};
+use crate::{GameAssets, GameState, PlayField, Score, StartRun};
+
+// Runs the gameplay plugins without a window or renderer, e.g. for CI, bots and balance runs
+pub struct HeadlessPlugin;
+
+impl HeadlessPlugin {
+    // The engine plugins the game needs when there is no window, used in place of `DefaultPlugins`
+    pub fn engine_plugins() -> PluginGroupBuilder {
+        MinimalPlugins
+            .build()
+            .add(LogPlugin::default())
+            .add(InputPlugin)
+            .add(StatesPlugin)
+            // Still needed for the gameplay tuning file
+            .add(AssetPlugin::default())
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // There is no window to take the bounds from, so use a fixed size play field
+        app.init_resource::<PlayField>()
+            // Nothing is rendered, so the sprites can use empty image handles
+            .init_resource::<GameAssets>()
+            // There are no splash or menu screens, start the game as soon as the tuning is loaded
+            .insert_state(GameState::Loading)
+            .add_systems(Startup, step_one_tick_per_update)
+            .add_systems(
+                Update,
+                start_when_tuning_loaded.run_if(in_state(GameState::Loading)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), exit_on_game_over);
+    }
+}
+
+// Advance the clock by exactly one simulation tick per update, so a run goes as fast as the
+// machine allows and its result doesn't depend on how fast that is
+fn step_one_tick_per_update(fixed_time: Res<Time<Fixed>>, mut commands: Commands) {
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(fixed_time.timestep()));
+}
+
+fn start_when_tuning_loaded(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    tuning_handle: Option<Res<TuningHandle>>,
+    asset_server: Res<AssetServer>,
+) {
+    if tuning::tuning_ready(&asset_server, tuning_handle.as_deref()) {
+        commands.trigger(StartRun);
+        next_state.set(GameState::Playing);
+    }
+}
+
+// Nobody is there to press fire, so report the result and stop
+fn exit_on_game_over(
+    score: Res<Score>,
+    rng: Res<GameRng>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    info!("Game over, final score: {} (seed {})", score.0, rng.seed());
+    app_exit_events.write(AppExit::Success);
+}
@@ -0,0 +1,525 @@
+use std::{fmt, fs, io, path::PathBuf, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::replay::ReplayPlayback;
+use crate::rng::GameRng;
+use crate::settings;
+use crate::{GameOverPrompt, GameState, Godmode, RESTART_PROMPT, Score, StartRun};
+
+// Number of runs kept in the table
+pub const MAX_HIGH_SCORES: usize = 10;
+
+// Bump this when the entries change. Files with another version are ignored
+pub const HIGH_SCORES_VERSION: u32 = 1;
+
+const HIGH_SCORES_FILE: &str = "highscores.ron";
+
+const INITIALS_LENGTH: usize = 3;
+
+// One run in the high score table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    pub initials: String,
+    // Seconds since the Unix epoch when the run ended
+    pub date: u64,
+    pub seed: u64,
+    // Seconds of gameplay
+    pub duration: f32,
+}
+
+impl HighScoreEntry {
+    // The day the run ended as `YYYY-MM-DD`, in UTC
+    pub fn date_string(&self) -> String {
+        // Civil date from days since 1970-01-01, see
+        // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = (self.date / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    // The length of the run as `M:SS`
+    pub fn duration_string(&self) -> String {
+        let seconds = self.duration as u64;
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+// The high score file as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct HighScoresFile {
+    version: u32,
+    entries: Vec<HighScoreEntry>,
+}
+
+#[derive(Debug)]
+pub enum HighScoresError {
+    Io(io::Error),
+    Ron(ron::error::SpannedError),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for HighScoresError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HighScoresError::Io(error) => write!(f, "{error}"),
+            HighScoresError::Ron(error) => write!(f, "could not parse high scores: {error}"),
+            HighScoresError::UnsupportedVersion(version) => write!(
+                f,
+                "high score version {version} is not supported, expected {HIGH_SCORES_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HighScoresError {}
+
+// The best runs, highest score first. Without a path the table only lasts until the game quits
+#[derive(Resource, Debug, Default)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    // `highscores.ron` in the platform config directory
+    pub fn default_path() -> Option<PathBuf> {
+        settings::config_dir().map(|dir| dir.join(HIGH_SCORES_FILE))
+    }
+
+    // Read the table saved at `path`. A missing file is an empty table, any other problem is
+    // logged and the table starts empty
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match Self::read(&path) {
+            Ok(entries) => entries,
+            Err(HighScoresError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                Vec::new()
+            }
+            Err(error) => {
+                warn!(
+                    "Could not load high scores from {}: {error}",
+                    path.display()
+                );
+                Vec::new()
+            }
+        };
+        let mut high_scores = Self {
+            path: Some(path),
+            entries,
+        };
+        // Don't trust the file to be sorted
+        high_scores
+            .entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        high_scores.entries.truncate(MAX_HIGH_SCORES);
+        high_scores
+    }
+
+    fn read(path: &PathBuf) -> Result<Vec<HighScoreEntry>, HighScoresError> {
+        let text = fs::read_to_string(path).map_err(HighScoresError::Io)?;
+        let file: HighScoresFile = ron::from_str(&text).map_err(HighScoresError::Ron)?;
+        if file.version != HIGH_SCORES_VERSION {
+            return Err(HighScoresError::UnsupportedVersion(file.version));
+        }
+        Ok(file.entries)
+    }
+
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    // The place in the table a run with this score would get, counting from 0
+    pub fn rank(&self, score: u32) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+        // Earlier runs keep their place on a tie
+        let rank = self
+            .entries
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(self.entries.len());
+        (rank < MAX_HIGH_SCORES).then_some(rank)
+    }
+
+    // Add a run to the table and save it. Returns its place, if it made it in
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self.rank(entry.score)?;
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        self.save();
+        Some(rank)
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let file = HighScoresFile {
+            version: HIGH_SCORES_VERSION,
+            entries: self.entries.clone(),
+        };
+        let text = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+            .expect("high scores serialize to RON");
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(path, text)),
+            None => fs::write(path, text),
+        };
+        if let Err(error) = result {
+            warn!("Could not save high scores to {}: {error}", path.display());
+        }
+    }
+}
+
+// Gameplay time of the current run. Pauses and menus don't count
+#[derive(Resource, Default)]
+pub struct RunTime(pub Duration);
+
+// Present while the player types their initials on the Game Over screen
+#[derive(Resource)]
+pub struct InitialsEntry {
+    letters: [u8; INITIALS_LENGTH],
+    cursor: usize,
+    rank: usize,
+}
+
+// Keeps the high score table, and asks for initials when a run makes it in. Saved to `path`
+// when it is set
+pub struct HighScoresPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.path {
+            Some(path) => app.insert_resource(HighScores::load(path.clone())),
+            None => app.init_resource::<HighScores>(),
+        };
+
+        app.init_resource::<RunTime>()
+            .add_observer(reset_run_time)
+            .add_systems(
+                FixedUpdate,
+                track_run_time.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                start_initials_entry.before(crate::display_game_over_ui),
+            )
+            .add_systems(
+                Update,
+                (
+                    (enter_initials, update_initials_prompt)
+                        .chain()
+                        .run_if(resource_exists::<InitialsEntry>),
+                    show_restart_prompt.run_if(resource_removed::<InitialsEntry>),
+                )
+                    .chain(),
+            )
+            .add_systems(OnExit(GameState::GameOver), end_initials_entry);
+    }
+}
+
+fn reset_run_time(_trigger: Trigger<StartRun>, mut run_time: ResMut<RunTime>) {
+    run_time.0 = Duration::ZERO;
+}
+
+fn track_run_time(mut run_time: ResMut<RunTime>, time: Res<Time>) {
+    run_time.0 += time.delta();
+}
+
+fn start_initials_entry(
+    mut commands: Commands,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    playback: Option<Res<ReplayPlayback>>,
+    godmode: Res<Godmode>,
+) {
+    // Watching a replay doesn't make a new high score, and neither does a run with godmode on.
+    // It can't be switched during a run, so it was on or off for all of it
+    if playback.is_some() || godmode.0 {
+        return;
+    }
+    if let Some(rank) = high_scores.rank(score.0) {
+        commands.insert_resource(InitialsEntry {
+            letters: [b'A'; INITIALS_LENGTH],
+            cursor: 0,
+            rank,
+        });
+    }
+}
+
+fn end_initials_entry(mut commands: Commands) {
+    commands.remove_resource::<InitialsEntry>();
+}
+
+// Arcade style: up and down pick the letter, left and right move between letters and fire
+// confirms one. Letters can also be typed on the keyboard
+#[allow(clippy::too_many_arguments)]
+fn enter_initials(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut entry: ResMut<InitialsEntry>,
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    rng: Res<GameRng>,
+    run_time: Res<RunTime>,
+) {
+    let gamepad = gamepads.iter().next();
+    let pressed = |keys: &[KeyCode], button: GamepadButton| {
+        keyboard_input.any_just_pressed(keys.iter().copied())
+            || gamepad.is_some_and(|gamepad| gamepad.just_pressed(button))
+    };
+
+    let cursor = entry.cursor;
+    if let Some(letter) = keyboard_input.get_just_pressed().find_map(typed_letter) {
+        entry.letters[cursor] = letter;
+        entry.cursor = (cursor + 1).min(INITIALS_LENGTH - 1);
+        // Typing the last letter doesn't submit, so it can still be corrected
+        return;
+    }
+    if pressed(&[KeyCode::ArrowUp], GamepadButton::DPadUp) {
+        entry.letters[cursor] = if entry.letters[cursor] == b'Z' {
+            b'A'
+        } else {
+            entry.letters[cursor] + 1
+        };
+    }
+    if pressed(&[KeyCode::ArrowDown], GamepadButton::DPadDown) {
+        entry.letters[cursor] = if entry.letters[cursor] == b'A' {
+            b'Z'
+        } else {
+            entry.letters[cursor] - 1
+        };
+    }
+    if pressed(
+        &[KeyCode::ArrowLeft, KeyCode::Backspace],
+        GamepadButton::DPadLeft,
+    ) || gamepad.is_some_and(|gamepad| gamepad.just_pressed(GamepadButton::East))
+    {
+        entry.cursor = cursor.saturating_sub(1);
+    }
+    if pressed(&[KeyCode::ArrowRight], GamepadButton::DPadRight) {
+        entry.cursor = (cursor + 1).min(INITIALS_LENGTH - 1);
+    }
+
+    if pressed(&[KeyCode::Space, KeyCode::Enter], GamepadButton::South) {
+        if cursor + 1 < INITIALS_LENGTH {
+            entry.cursor += 1;
+            return;
+        }
+
+        let date = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let initials = String::from_utf8_lossy(&entry.letters).into_owned();
+        high_scores.insert(HighScoreEntry {
+            score: score.0,
+            initials: initials.clone(),
+            date,
+            seed: rng.seed(),
+            duration: run_time.0.as_secs_f32(),
+        });
+        info!("Saved high score {} by {initials}", score.0);
+        commands.remove_resource::<InitialsEntry>();
+    }
+}
+
+fn typed_letter(key: &KeyCode) -> Option<u8> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA,
+        KeyCode::KeyB,
+        KeyCode::KeyC,
+        KeyCode::KeyD,
+        KeyCode::KeyE,
+        KeyCode::KeyF,
+        KeyCode::KeyG,
+        KeyCode::KeyH,
+        KeyCode::KeyI,
+        KeyCode::KeyJ,
+        KeyCode::KeyK,
+        KeyCode::KeyL,
+        KeyCode::KeyM,
+        KeyCode::KeyN,
+        KeyCode::KeyO,
+        KeyCode::KeyP,
+        KeyCode::KeyQ,
+        KeyCode::KeyR,
+        KeyCode::KeyS,
+        KeyCode::KeyT,
+        KeyCode::KeyU,
+        KeyCode::KeyV,
+        KeyCode::KeyW,
+        KeyCode::KeyX,
+        KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    LETTERS
+        .iter()
+        .position(|letter| letter == key)
+        .map(|index| b'A' + index as u8)
+}
+
+// The prompt is part of the Game Over text, see `display_game_over_ui`
+pub fn initials_prompt(entry: &InitialsEntry) -> String {
+    let letters: Vec<String> = entry
+        .letters
+        .iter()
+        .enumerate()
+        .map(|(index, letter)| {
+            if index == entry.cursor {
+                format!("[{}]", *letter as char)
+            } else {
+                format!(" {} ", *letter as char)
+            }
+        })
+        .collect();
+    format!(
+        "New high score, #{}!\nEnter your initials: {}",
+        entry.rank + 1,
+        letters.join("")
+    )
+}
+
+fn update_initials_prompt(
+    entry: Res<InitialsEntry>,
+    mut text_query: Query<&mut Text, With<GameOverPrompt>>,
+) {
+    if entry.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.0 = initials_prompt(&entry);
+        }
+    }
+}
+
+fn show_restart_prompt(mut text_query: Query<&mut Text, With<GameOverPrompt>>) {
+    for mut text in text_query.iter_mut() {
+        text.0 = RESTART_PROMPT.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, initials: &str) -> HighScoreEntry {
+        HighScoreEntry {
+            score,
+            initials: initials.to_string(),
+            date: 0,
+            seed: 0,
+            duration: 0.0,
+        }
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u32> {
+        high_scores
+            .entries()
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn empty_table_ranks_any_score_first() {
+        let high_scores = HighScores::default();
+        assert_eq!(high_scores.rank(1), Some(0));
+        assert_eq!(high_scores.rank(u32::MAX), Some(0));
+    }
+
+    #[test]
+    fn zero_never_makes_it_in() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.rank(0), None);
+        assert_eq!(high_scores.insert(entry(0, "AAA")), None);
+        assert!(high_scores.entries().is_empty());
+    }
+
+    #[test]
+    fn highest_score_first() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(entry(300, "AAA")), Some(0));
+        assert_eq!(high_scores.insert(entry(100, "BBB")), Some(1));
+        assert_eq!(high_scores.insert(entry(200, "CCC")), Some(1));
+        assert_eq!(high_scores.insert(entry(400, "DDD")), Some(0));
+        assert_eq!(scores(&high_scores), [400, 300, 200, 100]);
+    }
+
+    #[test]
+    fn earlier_runs_keep_their_place_on_a_tie() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry(200, "OLD"));
+        high_scores.insert(entry(100, "LOW"));
+        assert_eq!(high_scores.insert(entry(200, "NEW")), Some(1));
+        let initials: Vec<_> = high_scores
+            .entries()
+            .iter()
+            .map(|entry| entry.initials.as_str())
+            .collect();
+        assert_eq!(initials, ["OLD", "NEW", "LOW"]);
+    }
+
+    #[test]
+    fn only_the_best_runs_are_kept() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            high_scores.insert(entry(score * 10, "AAA"));
+        }
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+
+        // Not better than the last place, or tied with it
+        assert_eq!(high_scores.rank(10), None);
+        assert_eq!(high_scores.insert(entry(5, "BBB")), None);
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+
+        // A better run pushes the last one out
+        assert_eq!(
+            high_scores.insert(entry(15, "CCC")),
+            Some(MAX_HIGH_SCORES - 1)
+        );
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries().last().unwrap().score, 15);
+        assert_eq!(high_scores.insert(entry(1000, "DDD")), Some(0));
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries().last().unwrap().score, 20);
+    }
+
+    #[test]
+    fn loaded_table_is_sorted_and_cut_to_size() {
+        let path =
+            std::env::temp_dir().join(format!("rusteroids-highscores-{}.ron", std::process::id()));
+        let file = HighScoresFile {
+            version: HIGH_SCORES_VERSION,
+            entries: (1..=15).map(|score| entry(score, "AAA")).collect(),
+        };
+        fs::write(&path, ron::to_string(&file).unwrap()).unwrap();
+        let high_scores = HighScores::load(path.clone());
+        let _ = fs::remove_file(&path);
+        assert_eq!(scores(&high_scores), [15, 14, 13, 12, 11, 10, 9, 8, 7, 6]);
+    }
+
+    #[test]
+    fn date_and_duration() {
+        let mut run = entry(100, "AAA");
+        run.date = 951_782_400;
+        run.duration = 125.9;
+        assert_eq!(run.date_string(), "2000-02-29");
+        assert_eq!(run.duration_string(), "2:05");
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::input::PlayerInput;
+use crate::player::{Player, PlayerVelocity, ShipDestroyed, ShipLoss};
+use crate::rng::GameRng;
+use crate::tuning::GameTuning;
+use crate::{GameplaySet, Godmode, PlayField};
+
+// Defaults, the values in play come from `GameTuning`
+pub const HYPERSPACE_COOLDOWN: f32 = 5.0;
+pub const HYPERSPACE_MALFUNCTION_CHANCE: f32 = 0.1;
+pub const HYPERSPACE_JUMP_TIME: f32 = 0.6;
+
+// The ship is in hyperspace: it shrinks away, reappears at `destination` halfway through and
+// grows back. Meanwhile it can't move, shoot or be hit. On a malfunction it never comes back
+#[derive(Component)]
+pub struct HyperspaceJump {
+    timer: Timer,
+    destination: Vec3,
+    malfunction: bool,
+    arrived: bool,
+}
+
+// Counts down until the ship can jump again
+#[derive(Component)]
+pub struct HyperspaceCooldown(pub Timer);
+
+pub struct HyperspacePlugin;
+
+impl Plugin for HyperspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (start_jump, tick_cooldown, jump).in_set(GameplaySet::Movement),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn start_jump(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    player_query: Query<
+        Entity,
+        (
+            With<Player>,
+            Without<HyperspaceJump>,
+            Without<HyperspaceCooldown>,
+        ),
+    >,
+    play_field: Res<PlayField>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+) {
+    if !input.hyperspace {
+        return;
+    }
+    let Ok(player) = player_query.single() else {
+        return;
+    };
+
+    // Both are drawn from the game's generator when the jump starts, so replays land in the
+    // same spot with the same outcome
+    let destination = Vec3::new(
+        rng.gen_range(-play_field.half_width()..=play_field.half_width()),
+        rng.gen_range(-play_field.half_height()..=play_field.half_height()),
+        0.0,
+    );
+    let malfunction = rng.gen_bool(tuning.hyperspace.malfunction_chance as f64);
+
+    let hyperspace = &tuning.hyperspace;
+    commands.entity(player).insert((
+        HyperspaceJump {
+            timer: Timer::from_seconds(hyperspace.jump_time, TimerMode::Once),
+            destination,
+            malfunction,
+            arrived: false,
+        },
+        HyperspaceCooldown(Timer::from_seconds(hyperspace.cooldown, TimerMode::Once)),
+    ));
+}
+
+fn tick_cooldown(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut HyperspaceCooldown)>,
+    time: Res<Time>,
+) {
+    for (entity, mut cooldown) in query.iter_mut() {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HyperspaceCooldown>();
+        }
+    }
+}
+
+fn jump(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut HyperspaceJump,
+        &mut Transform,
+        &mut PlayerVelocity,
+    )>,
+    godmode: Res<Godmode>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (entity, mut jump, mut transform, mut velocity) in query.iter_mut() {
+        jump.timer.tick(time.delta());
+        let progress = jump.timer.fraction();
+
+        if !jump.arrived && progress >= 0.5 {
+            if jump.malfunction && !godmode.0 {
+                commands.trigger_targets(ShipDestroyed(ShipLoss::Malfunction), entity);
+                continue;
+            }
+            jump.arrived = true;
+            transform.translation = jump.destination;
+            if !tuning.hyperspace.keep_velocity {
+                velocity.0 = Vec2::ZERO;
+            }
+        }
+
+        if jump.timer.finished() {
+            commands.entity(entity).remove::<HyperspaceJump>();
+            transform.scale = Vec3::ONE;
+        } else {
+            // Shrinks to nothing on the way out, grows back on the way in
+            transform.scale = Vec3::splat((1.0 - 2.0 * progress).abs());
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+// What the player wants the ship to do. It is sampled from keyboard and gamepad every frame and
+// read by the simulation on each fixed tick, so no gameplay system touches the devices directly
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerInput {
+    // Positive turns left, in -1.0..=1.0
+    pub rotation: f32,
+    // Positive is forward, negative reverse thrust or the brake, in -1.0..=1.0
+    pub thrust: f32,
+    // Latched until a tick has used it, so a tap between two ticks still fires
+    pub fire: bool,
+    // Whether fire is held down, for automatic fire
+    pub fire_held: bool,
+    // Latched like `fire`
+    pub hyperspace: bool,
+}
+
+// How far a stick has to move before it counts
+const STICK_DEAD_ZONE: f32 = 0.1;
+
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .add_systems(
+                RunFixedMainLoop,
+                read_player_input.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+            )
+            .add_systems(FixedPostUpdate, consume_presses);
+    }
+}
+
+fn read_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let mut rotation_input = 0.0;
+    let mut thrust_input = 0.0;
+    let mut fire_input = keyboard_input.just_pressed(KeyCode::Space);
+    let mut fire_held = keyboard_input.pressed(KeyCode::Space);
+    let mut hyperspace_input =
+        keyboard_input.any_just_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    // Keyboard
+    if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
+        rotation_input += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
+        rotation_input -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
+        thrust_input += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
+        thrust_input -= 1.0;
+    }
+
+    // Gamepad
+    if let Some(gamepad) = gamepads.iter().next() {
+        if gamepad.pressed(GamepadButton::DPadLeft) {
+            rotation_input += 1.0;
+        }
+        if gamepad.pressed(GamepadButton::DPadRight) {
+            rotation_input -= 1.0;
+        }
+        if gamepad.pressed(GamepadButton::DPadUp) {
+            thrust_input += 1.0;
+        }
+        if gamepad.pressed(GamepadButton::DPadDown) {
+            thrust_input -= 1.0;
+        }
+        if gamepad.just_pressed(GamepadButton::South) {
+            fire_input = true;
+        }
+        if gamepad.pressed(GamepadButton::South) {
+            fire_held = true;
+        }
+        if gamepad.just_pressed(GamepadButton::North) {
+            hyperspace_input = true;
+        }
+
+        if let Some(left_stick_x) = gamepad.get(GamepadAxis::LeftStickX)
+            && left_stick_x.abs() > STICK_DEAD_ZONE
+        {
+            rotation_input -= left_stick_x;
+        }
+        // Thrust grows with how far the stick is pushed, from nothing at the dead zone
+        if let Some(left_stick_y) = gamepad.get(GamepadAxis::LeftStickY)
+            && left_stick_y.abs() > STICK_DEAD_ZONE
+        {
+            let push = (left_stick_y.abs() - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE);
+            thrust_input += push.min(1.0) * left_stick_y.signum();
+        }
+    }
+
+    input.rotation = rotation_input.clamp(-1.0, 1.0);
+    input.thrust = thrust_input.clamp(-1.0, 1.0);
+    input.fire_held = fire_held;
+    input.fire |= fire_input;
+    input.hyperspace |= hyperspace_input;
+}
+
+fn consume_presses(mut input: ResMut<PlayerInput>) {
+    input.fire = false;
+    input.hyperspace = false;
+}
@@ -0,0 +1,1019 @@
+use bevy::diagnostic::FrameCount;
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PresentMode, Window, WindowMode, WindowResized};
+use highscores::{HighScores, InitialsEntry};
+use rand::Rng;
+use replay::{Replay, ReplayPlayback};
+use rng::{GameRng, SeedSetting};
+use serde::{Deserialize, Serialize};
+use settings::{AppSettingsExt, Setting, SettingsStore};
+use std::path::PathBuf;
+use std::time::Duration;
+use tuning::{GameTuning, TuningHandle};
+
+pub mod asteroid;
+pub mod collider;
+pub mod collision;
+pub mod edges;
+pub mod effects;
+pub mod events;
+pub mod headless;
+pub mod highscores;
+pub mod hyperspace;
+pub mod input;
+pub mod mechanics;
+pub mod player;
+pub mod powerup;
+pub mod replay;
+pub mod rng;
+pub mod saucer;
+pub mod scoring;
+pub mod settings;
+pub mod spatial;
+pub mod stats;
+pub mod timestep;
+pub mod tuning;
+pub mod wave;
+pub mod weapon;
+
+pub mod menu;
+pub mod pause;
+pub mod splash;
+
+#[derive(Component)]
+struct Background;
+
+// Tag component for the background and HUD, which stay up from loading until the player quits
+// to the menu
+#[derive(Component)]
+pub(crate) struct OnGameScreen;
+
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum GameState {
+    #[default] // <-- This is now the starting state
+    Splash,
+    Menu,
+    Loading,
+    Playing,
+    // The run is frozen with the pause menu on top
+    Paused,
+    GameOver,
+}
+
+// The steps of one simulation tick in `FixedUpdate`, in the order they run. Keeping the order
+// fixed makes a run reproducible from its seed and inputs
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    Input,
+    Movement,
+    Bounds,
+    Collision,
+    Spawning,
+}
+
+// One of the settings that can be set through the menu. It will be a resource in the app
+#[derive(
+    Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Godmode(pub bool);
+
+impl Setting for Godmode {
+    const KEY: &'static str = "godmode";
+}
+
+// One of the settings that can be set through the menu. It will be a resource in the app
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Volume(pub u32);
+
+impl Default for Volume {
+    fn default() -> Self {
+        Volume(7)
+    }
+}
+
+impl Setting for Volume {
+    const KEY: &'static str = "volume";
+}
+
+// One of the settings that can be set through the menu. It will be a resource in the app.
+// Slows the ship down whenever it isn't thrusting, see `player::player_movement`
+#[derive(
+    Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct InertialDampeners(pub bool);
+
+impl Setting for InertialDampeners {
+    const KEY: &'static str = "inertial_dampeners";
+}
+
+#[derive(Component)]
+struct GameOverUi;
+
+// The line of the Game Over screen that tells the player what to do next
+#[derive(Component)]
+pub(crate) struct GameOverPrompt;
+
+pub(crate) const RESTART_PROMPT: &str = "Press fire to play again";
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct LivesText;
+
+#[derive(Component)]
+struct WaveText;
+
+#[derive(Component)]
+struct ComboText;
+
+#[derive(Component)]
+struct PowerUpText;
+
+#[derive(Resource, Default)]
+pub struct GameAssets {
+    pub player: Handle<Image>,
+    pub asteroid: Handle<Image>,
+}
+
+#[derive(Resource)]
+pub struct Score(pub u32);
+
+// Ships left in the current run, including the one in play
+#[derive(Resource)]
+pub struct Lives(pub u32);
+
+#[derive(Resource)]
+pub struct AsteroidSpawnTimer(pub Timer);
+
+// Quit after this many frames, for scripted runs
+#[derive(Resource)]
+struct MaxFrames(u32);
+
+// Trigger this to throw away the current run, if any, and set up a fresh one. Every way of
+// starting a game goes through it, so a new run always begins from the same clean state
+#[derive(Event)]
+pub struct StartRun;
+
+// The area the game is played in, centered on the origin. Gameplay systems take their bounds
+// from here instead of the window, so they also run headless
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PlayField {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PlayField {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn half_width(&self) -> f32 {
+        self.width / 2.0
+    }
+
+    pub fn half_height(&self) -> f32 {
+        self.height / 2.0
+    }
+}
+
+impl Default for PlayField {
+    fn default() -> Self {
+        Self::new(800.0, 600.0)
+    }
+}
+
+fn exit_after_max_frames(
+    frame_count: Res<FrameCount>,
+    max_frames: Res<MaxFrames>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if frame_count.0 >= max_frames.0 {
+        info!("Quitting after {} frames", max_frames.0);
+        app_exit_events.write(AppExit::Success);
+    }
+}
+
+// Generic system that takes a component as a parameter, and will despawn all entities with that component
+fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn setup_background(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut windows: Query<&mut Window>,
+) {
+    let window = windows.single_mut().unwrap();
+    let background_handle: Handle<Image> = asset_server.load("background.png");
+
+    commands.spawn((
+        // The image handle is a component
+        // The sprite component defines how to render the image
+        Sprite {
+            image: background_handle.clone(),
+
+            // Scale the sprite to fill the entire window
+            custom_size: Some(Vec2::new(window.width(), window.height())),
+            ..default()
+        },
+        // The transform component defines the position
+        // Set Z to a negative value to ensure it's drawn behind other sprites
+        Transform::from_xyz(0.0, 0.0, -1.0),
+        Background,
+        OnGameScreen,
+    ));
+}
+
+fn resize_background(
+    mut resize_reader: EventReader<WindowResized>,
+    mut background_query: Query<&mut Sprite, With<Background>>,
+) {
+    for e in resize_reader.read() {
+        if let Ok(mut sprite) = background_query.single_mut() {
+            sprite.custom_size = Some(Vec2::new(e.width, e.height));
+        }
+    }
+}
+
+// Keep the play field the same size as the window, except during replay playback where it
+// stays the size the run was recorded with
+fn resize_play_field(
+    mut resize_reader: EventReader<WindowResized>,
+    mut play_field: ResMut<PlayField>,
+) {
+    for e in resize_reader.read() {
+        *play_field = PlayField::new(e.width, e.height);
+    }
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameAssets {
+        player: asset_server.load("sprites/player.png"),
+        asteroid: asset_server.load("sprites/asteroid.png"),
+    });
+}
+
+fn check_assets_loaded(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    game_assets: Res<GameAssets>,
+    tuning_handle: Option<Res<TuningHandle>>,
+    asset_server: Res<AssetServer>,
+) {
+    let player_loaded = asset_server.is_loaded_with_dependencies(&game_assets.player);
+    let asteroid_loaded = asset_server.is_loaded_with_dependencies(&game_assets.asteroid);
+    let tuning_loaded = tuning::tuning_ready(&asset_server, tuning_handle.as_deref());
+
+    if player_loaded && asteroid_loaded && tuning_loaded {
+        // All assets are now loaded, we can start the run and transition to the Playing state
+        commands.trigger(StartRun);
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn((Camera2d, Msaa::Off));
+}
+
+fn setup_score_display(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font_handle: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    // Spawn the root node for positioning
+    commands
+        .spawn((
+            // This Node component positions the score in the top-left corner
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            // It's good practice to give root UI nodes a transparent background
+            BackgroundColor(Color::NONE),
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            // Spawn the text entity as a child
+            parent.spawn((
+                Text::new("Score: 0"),
+                TextFont {
+                    font: font_handle.clone(),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ScoreText, // The marker component to find and update this text
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font_handle.clone(),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::left(Val::Px(30.0)),
+                    ..default()
+                },
+                LivesText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font_handle.clone(),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::left(Val::Px(30.0)),
+                    ..default()
+                },
+                WaveText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font_handle.clone(),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.8, 0.2)),
+                Node {
+                    margin: UiRect::left(Val::Px(30.0)),
+                    ..default()
+                },
+                ComboText,
+            ));
+        });
+
+    // Active power-ups, one line each under the score
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(50.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        OnGameScreen,
+        children![(
+            Text::new(""),
+            TextFont {
+                font: font_handle,
+                font_size: 22.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.6, 0.9, 1.0)),
+            PowerUpText,
+        )],
+    ));
+}
+
+fn update_score_display(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if let Ok(mut text) = query.single_mut() {
+        // CORRECTED: Access the sections Vec with .0
+        text.0 = format!("Score: {}", score.0);
+    }
+}
+
+fn update_lives_display(
+    lives: Res<Lives>,
+    score: Res<Score>,
+    tuning: Res<GameTuning>,
+    mut query: Query<&mut Text, With<LivesText>>,
+) {
+    if let Ok(mut text) = query.single_mut() {
+        let display = match scoring::next_extra_life(&tuning, score.0) {
+            Some(next) => format!("Lives: {} (next at {next})", lives.0),
+            None => format!("Lives: {}", lives.0),
+        };
+        if text.0 != display {
+            text.0 = display;
+        }
+    }
+}
+
+fn update_combo_display(
+    combo: Res<scoring::Combo>,
+    tuning: Res<GameTuning>,
+    mut query: Query<&mut Text, With<ComboText>>,
+) {
+    if let Ok(mut text) = query.single_mut() {
+        // Only worth showing once it adds something
+        let display = match combo.multiplier(&tuning) {
+            1 => String::new(),
+            multiplier => format!("Combo x{multiplier}"),
+        };
+        if text.0 != display {
+            text.0 = display;
+        }
+    }
+}
+
+fn update_power_up_display(
+    player_query: Query<&powerup::ActivePowerUps, With<player::Player>>,
+    mut query: Query<&mut Text, With<PowerUpText>>,
+) {
+    let Ok(mut text) = query.single_mut() else {
+        return;
+    };
+    let display = player_query
+        .single()
+        .map(|power_ups| {
+            power_ups
+                .remaining()
+                .map(|(kind, seconds)| format!("{} {seconds:.1}s", kind.name()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    if text.0 != display {
+        text.0 = display;
+    }
+}
+
+fn update_wave_display(
+    wave: Res<wave::Wave>,
+    tuning: Res<GameTuning>,
+    mut query: Query<&mut Text, With<WaveText>>,
+) {
+    if let Ok(mut text) = query.single_mut() {
+        // Waves don't mean anything when asteroids trickle in
+        let display = match tuning.asteroid.spawn_mode {
+            wave::SpawnMode::Waves => format!("Wave: {}", wave.number),
+            wave::SpawnMode::Trickle => String::new(),
+        };
+        if text.0 != display {
+            text.0 = display;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_asteroids_over_time(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<AsteroidSpawnTimer>,
+    play_field: Res<PlayField>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    theme: Res<asteroid::AsteroidTheme>,
+    assets: Res<GameAssets>,
+) {
+    // In the waves mode new asteroids only come with the next wave
+    if tuning.asteroid.spawn_mode != wave::SpawnMode::Trickle {
+        return;
+    }
+
+    // Pick up changes to the spawn interval from the tuning file
+    let interval = Duration::from_secs_f32(tuning.asteroid.spawn_interval);
+    if timer.0.duration() != interval {
+        timer.0.set_duration(interval);
+    }
+
+    // Tick the timer
+    timer.0.tick(time.delta());
+
+    // If the timer just finished, spawn a new asteroid
+    if timer.0.just_finished() {
+        let half_width = play_field.half_width();
+        let half_height = play_field.half_height();
+
+        // Choose a random edge of the screen to spawn from
+        let edge = rng.gen_range(0..4);
+        let (x, y) = match edge {
+            0 => (rng.gen_range(-half_width..half_width), half_height + 50.0), // Top
+            1 => (rng.gen_range(-half_width..half_width), -half_height - 50.0), // Bottom
+            2 => (-half_width - 50.0, rng.gen_range(-half_height..half_height)), // Left
+            _ => (half_width + 50.0, rng.gen_range(-half_height..half_height)), // Right
+        };
+        let position = Vec3::new(x, y, 0.0);
+
+        // Aim the asteroid towards the center with some randomness
+        let direction_to_center = (Vec3::ZERO - position).normalize_or_zero();
+        let angle_offset = rng.gen_range(-0.5..0.5); // Approx +/- 28 degrees
+        let final_direction = Quat::from_rotation_z(angle_offset) * direction_to_center;
+
+        let speed = tuning.asteroid.large_speed;
+        let velocity = final_direction.truncate() * speed;
+
+        // Use your existing helper function to spawn the asteroid
+        asteroid::spawn_asteroid(
+            &mut commands,
+            asteroid::AsteroidSize::Large,
+            position,
+            velocity,
+            &mut rng,
+            &tuning.asteroid,
+            asteroid::AsteroidLook {
+                theme: *theme,
+                sprite: &assets.asteroid,
+            },
+        );
+    }
+}
+
+fn display_game_over_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rng: Res<GameRng>,
+    initials_entry: Option<Res<InitialsEntry>>,
+) {
+    let bold_font: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let prompt = match initials_entry {
+        Some(entry) => highscores::initials_prompt(&entry),
+        None => RESTART_PROMPT.to_string(),
+    };
+
+    // Spawn the root UI entity
+    commands
+        .spawn((
+            Name::new("Game Over UI"),
+            GameOverUi,
+            // The Node component defines the layout box.
+            Node {
+                // Fill the entire window
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                // Center its children, one below the other
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // A transparent background for the root node.
+            BackgroundColor(Color::NONE),
+        ))
+        // Use the new `children!` macro to spawn the text entity.
+        .with_children(|parent| {
+            parent.spawn((
+                // The text content.
+                Text::new(format!("Game Over!\nSeed: {}", rng.seed())),
+                // Set the font.
+                TextFont {
+                    font: bold_font.clone(),
+                    font_size: 40.0,
+                    ..Default::default()
+                },
+                // Set the color.
+                TextColor(Color::WHITE),
+                // Set the text alignment (replaces the old `.with_text_justify`).
+                TextLayout::new(JustifyText::Center, LineBreak::NoWrap),
+            ));
+            // What to do next, restart or enter initials for a new high score
+            parent.spawn((
+                Text::new(prompt),
+                TextFont {
+                    font: bold_font,
+                    font_size: 40.0,
+                    ..Default::default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new(JustifyText::Center, LineBreak::NoWrap),
+                GameOverPrompt,
+            ));
+        });
+}
+
+fn despawn_game_over_ui(
+    mut commands: Commands,
+    game_over_ui_query: Query<Entity, With<GameOverUi>>,
+) {
+    for entity in game_over_ui_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn handle_game_over_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let mut restart = keyboard_input.just_pressed(KeyCode::Space);
+
+    if !restart
+        && let Some(gamepad) = gamepads.iter().next()
+        && gamepad.just_pressed(GamepadButton::South)
+    {
+        restart = true;
+    }
+
+    if restart {
+        // Clear the old run and spawn a new one
+        commands.trigger(StartRun);
+
+        // Transition to Playing state
+        game_state.set(GameState::Playing);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn start_run(
+    _trigger: Trigger<StartRun>,
+    mut commands: Commands,
+    game_entities: Query<
+        Entity,
+        Or<(
+            With<player::Player>,
+            With<mechanics::Bullet>,
+            With<asteroid::Asteroid>,
+            With<saucer::Saucer>,
+            With<powerup::PowerUp>,
+        )>,
+    >,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut respawn: ResMut<player::PlayerRespawn>,
+    mut spawn_timer: ResMut<AsteroidSpawnTimer>,
+    mut wave: ResMut<wave::Wave>,
+    play_field: Res<PlayField>,
+    seed_setting: Res<SeedSetting>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    theme: Res<asteroid::AsteroidTheme>,
+    assets: Res<GameAssets>,
+) {
+    // Despawn all game entities
+    for entity in game_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // Reset score, lives and timers
+    score.0 = 0;
+    lives.0 = tuning.player.lives;
+    respawn.0 = None;
+    spawn_timer.0.reset();
+    *wave = wave::Wave::default();
+
+    // Every run starts from a known seed so it can be reproduced
+    rng.reseed(seed_setting.next_seed());
+    info!("Starting run with seed {}", rng.seed());
+
+    player::spawn_player(&mut commands, &assets.player, &tuning);
+    wave::spawn_wave(
+        &mut commands,
+        wave.number,
+        &play_field,
+        &mut rng,
+        &tuning,
+        Vec3::ZERO,
+        asteroid::AsteroidLook {
+            theme: *theme,
+            sprite: &assets.asteroid,
+        },
+    );
+}
+
+// The whole game as one plugin, so it can be embedded in a launcher or an integration test.
+// Configure it with the builder methods, e.g.
+// `RusteroidsPlugin::new().with_window_size(1280.0, 720.0).skip_splash()`
+pub struct RusteroidsPlugin {
+    window_size: Vec2,
+    fullscreen: bool,
+    starting_state: GameState,
+    asset_root: String,
+    headless: bool,
+    default_plugins: bool,
+    seed: Option<u64>,
+    timestep: Duration,
+    godmode: Option<bool>,
+    volume: Option<u32>,
+    persist_settings: bool,
+    max_frames: Option<u32>,
+    playback: Option<Replay>,
+    replay_dir: Option<PathBuf>,
+}
+
+impl Default for RusteroidsPlugin {
+    fn default() -> Self {
+        Self {
+            window_size: Vec2::new(800.0, 600.0),
+            fullscreen: false,
+            starting_state: GameState::Splash,
+            asset_root: "assets".to_string(),
+            headless: false,
+            default_plugins: true,
+            seed: None,
+            timestep: timestep::TimestepPlugin::default().timestep,
+            godmode: None,
+            volume: None,
+            persist_settings: true,
+            max_frames: None,
+            playback: None,
+            replay_dir: None,
+        }
+    }
+}
+
+impl RusteroidsPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Size of the window, which is also the size of the play field
+    pub fn with_window_size(mut self, width: f32, height: f32) -> Self {
+        self.window_size = Vec2::new(width, height);
+        self
+    }
+
+    // Borderless fullscreen on the current monitor. The play field follows the window size
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    // State to launch in. `Playing` goes through `Loading` first so the assets are ready
+    pub fn with_starting_state(mut self, state: GameState) -> Self {
+        self.starting_state = state;
+        self
+    }
+
+    // Launch in the main menu instead of on the splash screen
+    pub fn skip_splash(mut self) -> Self {
+        if self.starting_state == GameState::Splash {
+            self.starting_state = GameState::Menu;
+        }
+        self
+    }
+
+    // Folder the assets are loaded from, relative to the executable or `CARGO_MANIFEST_DIR`
+    pub fn with_asset_root(mut self, asset_root: impl Into<String>) -> Self {
+        self.asset_root = asset_root.into();
+        self
+    }
+
+    // Run the simulation without a window or renderer, see `headless::HeadlessPlugin`
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    // Whether to add Bevy's `DefaultPlugins` (or `MinimalPlugins` when headless). Turn it off
+    // when the app already has them, the window size and asset root are then up to the app
+    pub fn with_default_plugins(mut self, default_plugins: bool) -> Self {
+        self.default_plugins = default_plugins;
+        self
+    }
+
+    // Play every run with this seed instead of a random one
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    // Number of simulation ticks per second
+    pub fn with_tick_rate(mut self, tick_rate: f64) -> Self {
+        self.timestep = timestep::TimestepPlugin::from_hz(tick_rate).timestep;
+        self
+    }
+
+    // Godmode for this run, instead of the saved setting
+    pub fn with_godmode(mut self, godmode: bool) -> Self {
+        self.godmode = Some(godmode);
+        self
+    }
+
+    // Sound volume for this run, from 0 to 9, instead of the saved setting
+    pub fn with_volume(mut self, volume: u32) -> Self {
+        self.volume = Some(volume.min(9));
+        self
+    }
+
+    // Whether to load the menu settings and high scores from the platform config directory and
+    // save them there when they change, and record runs to the platform data directory.
+    // Headless runs never touch these files
+    pub fn with_persistent_settings(mut self, persist_settings: bool) -> Self {
+        self.persist_settings = persist_settings;
+        self
+    }
+
+    // Quit after this many frames, e.g. for smoke tests and demo recordings
+    pub fn with_max_frames(mut self, max_frames: Option<u32>) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    // Play back a recorded run instead of reading the input devices. The replay brings the
+    // seed, tick rate, play field size and settings it was recorded with, and skips straight
+    // to the game
+    pub fn with_replay(mut self, replay: Replay) -> Self {
+        self.playback = Some(replay);
+        self
+    }
+
+    // Record every run to `dir`, also when headless or without persistent settings
+    pub fn with_replay_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.replay_dir = Some(dir.into());
+        self
+    }
+}
+
+impl Plugin for RusteroidsPlugin {
+    fn build(&self, app: &mut App) {
+        // A replay brings the play field and settings it was recorded with
+        let play_field = match &self.playback {
+            Some(replay) => replay.play_field,
+            None => PlayField::new(self.window_size.x, self.window_size.y),
+        };
+        let (seed, timestep, godmode, dampeners, asteroid_theme) = match &self.playback {
+            Some(replay) => (
+                Some(replay.seed),
+                replay.timestep,
+                Some(replay.godmode),
+                Some(replay.inertial_dampeners),
+                Some(replay.asteroid_theme),
+            ),
+            None => (self.seed, self.timestep, self.godmode, None, None),
+        };
+
+        if self.headless {
+            if self.default_plugins {
+                app.add_plugins(headless::HeadlessPlugin::engine_plugins().set(AssetPlugin {
+                    file_path: self.asset_root.clone(),
+                    ..default()
+                }));
+            }
+            app.insert_resource(play_field)
+                .add_plugins(headless::HeadlessPlugin);
+        } else {
+            if self.default_plugins {
+                app.add_plugins(
+                    DefaultPlugins
+                        .set(WindowPlugin {
+                            primary_window: Some(Window {
+                                resolution: (play_field.width, play_field.height).into(),
+                                mode: if self.fullscreen {
+                                    WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+                                } else {
+                                    WindowMode::Windowed
+                                },
+                                title: "Rusteroids".to_string(),
+                                present_mode: PresentMode::AutoNoVsync,
+                                ..default()
+                            }),
+                            ..default()
+                        })
+                        .set(AssetPlugin {
+                            file_path: self.asset_root.clone(),
+                            ..default()
+                        }),
+                );
+            }
+
+            // The game can't be played before its assets are loaded
+            let starting_state = match self.starting_state {
+                _ if self.playback.is_some() => GameState::Loading,
+                GameState::Playing | GameState::Paused | GameState::GameOver => GameState::Loading,
+                ref state => state.clone(),
+            };
+
+            app.insert_state(starting_state)
+                .insert_resource(play_field)
+                .add_systems(Startup, setup_camera)
+                .add_systems(
+                    OnEnter(GameState::Loading),
+                    (setup_background, load_assets, setup_score_display),
+                )
+                // This system now runs every frame ONLY when in the Loading state
+                .add_systems(
+                    Update,
+                    check_assets_loaded.run_if(in_state(GameState::Loading)),
+                )
+                // Quitting to the menu throws the run away
+                .add_systems(
+                    OnEnter(GameState::Menu),
+                    (
+                        despawn_screen::<OnGameScreen>,
+                        despawn_screen::<player::Player>,
+                        despawn_screen::<asteroid::Asteroid>,
+                        despawn_screen::<mechanics::Bullet>,
+                        despawn_screen::<saucer::Saucer>,
+                        despawn_screen::<powerup::PowerUp>,
+                    ),
+                )
+                .add_systems(OnEnter(GameState::GameOver), display_game_over_ui)
+                .add_systems(OnExit(GameState::GameOver), despawn_game_over_ui)
+                .add_systems(
+                    Update,
+                    (
+                        // Fire confirms the initials while they are being entered
+                        handle_game_over_input
+                            .run_if(in_state(GameState::GameOver))
+                            .run_if(not(resource_exists::<InitialsEntry>)),
+                        // The HUD is only redrawn when what it shows changes
+                        update_score_display
+                            .run_if(in_state(GameState::Playing).and(resource_changed::<Score>)),
+                        update_lives_display.run_if(
+                            in_state(GameState::Playing).and(
+                                resource_changed::<Lives>
+                                    .or(resource_changed::<Score>)
+                                    .or(resource_changed::<GameTuning>),
+                            ),
+                        ),
+                        update_wave_display.run_if(in_state(GameState::Playing).and(
+                            resource_changed::<wave::Wave>.or(resource_changed::<GameTuning>),
+                        )),
+                        update_combo_display.run_if(in_state(GameState::Playing).and(
+                            resource_changed::<scoring::Combo>.or(resource_changed::<GameTuning>),
+                        )),
+                        update_power_up_display.run_if(
+                            in_state(GameState::Playing).and(
+                                any_match_filter::<Changed<powerup::ActivePowerUps>>
+                                    .or(any_component_removed::<powerup::ActivePowerUps>),
+                            ),
+                        ),
+                        resize_background,
+                        resize_play_field.run_if(not(resource_exists::<ReplayPlayback>)),
+                    ),
+                )
+                .add_plugins(highscores::HighScoresPlugin {
+                    path: HighScores::default_path().filter(|_| self.persist_settings),
+                })
+                .add_plugins((
+                    splash::splash_plugin,
+                    menu::menu_plugin,
+                    pause::pause_plugin,
+                    wave::wave_banner_plugin,
+                    asteroid::asteroid_outline_plugin,
+                    effects::EffectsPlugin,
+                ));
+        }
+
+        if let Some(max_frames) = self.max_frames {
+            app.insert_resource(MaxFrames(max_frames))
+                .add_systems(Last, exit_after_max_frames);
+        }
+
+        // Saved settings would make headless runs depend on the machine they run on
+        let settings_path =
+            SettingsStore::default_path().filter(|_| self.persist_settings && !self.headless);
+        match settings_path {
+            Some(path) => app.insert_resource(SettingsStore::load(path)),
+            None => app.init_resource::<SettingsStore>(),
+        };
+
+        app.register_setting(godmode.map(Godmode))
+            .register_setting(dampeners.map(InertialDampeners))
+            .register_setting(asteroid_theme)
+            .register_setting(self.volume.map(Volume))
+            .insert_resource(SeedSetting(seed))
+            .init_resource::<GameRng>()
+            .insert_resource(Score(0))
+            .insert_resource(Lives(player::PLAYER_LIVES))
+            .insert_resource(AsteroidSpawnTimer(Timer::from_seconds(
+                asteroid::ASTEROID_SPAWN_INTERVAL,
+                TimerMode::Repeating,
+            )))
+            .add_plugins(tuning::TuningPlugin {
+                fixed: self.playback.as_ref().map(|replay| replay.tuning.clone()),
+            })
+            .add_plugins(timestep::TimestepPlugin { timestep })
+            .add_plugins(input::PlayerInputPlugin)
+            .add_plugins(replay::ReplayPlugin {
+                playback: self.playback.clone(),
+                record_dir: self.replay_dir.clone().or_else(|| {
+                    Replay::default_dir().filter(|_| self.persist_settings && !self.headless)
+                }),
+            })
+            .configure_sets(
+                FixedUpdate,
+                (
+                    GameplaySet::Input,
+                    GameplaySet::Movement,
+                    GameplaySet::Bounds,
+                    GameplaySet::Collision,
+                    GameplaySet::Spawning,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_observer(start_run)
+            .add_systems(
+                FixedUpdate,
+                spawn_asteroids_over_time
+                    .in_set(GameplaySet::Spawning)
+                    .after(weapon::fire_weapon),
+            )
+            .add_plugins(asteroid::AsteroidPlugin)
+            .add_plugins(wave::WavePlugin)
+            .add_plugins(scoring::ScoringPlugin)
+            .add_plugins(stats::StatsPlugin)
+            .add_plugins(player::PlayerPlugin)
+            .add_plugins(hyperspace::HyperspacePlugin)
+            .add_plugins(saucer::SaucerPlugin)
+            .add_plugins(powerup::PowerUpPlugin)
+            .add_plugins(weapon::WeaponPlugin)
+            .add_plugins(spatial::SpatialHashPlugin)
+            .add_plugins(collision::CollisionPlugin)
+            .add_plugins(mechanics::MechanicsPlugin)
+            .add_plugins(edges::EdgesPlugin);
+    }
+}
@@ -1,12 +1,22 @@
 use crate::asteroid::{
-    ASTEROID_MEDIUM_SPEED, ASTEROID_SMALL_SPEED, Asteroid, AsteroidSize, spawn_asteroid,
+    Asteroid, AsteroidLook, AsteroidSize, AsteroidTheme, AsteroidVelocity, split_asteroid,
 };
-use crate::{GameAssets, GameState, Score};
+use crate::collider::Collider;
+use crate::collision::{BulletHitAsteroid, Contacts, PlayerHitAsteroid};
+use crate::edges::EdgeKind;
+use crate::events::{AsteroidDestroyed, Shooter};
+use crate::hyperspace::HyperspaceJump;
+use crate::player::{ShipDestroyed, ShipLoss};
+use crate::powerup::{self, ActivePowerUps, Piercing, PowerUpKind};
+use crate::rng::GameRng;
+use crate::saucer::SaucerBullet;
+use crate::scoring::Combo;
+use crate::tuning::GameTuning;
+use crate::{GameAssets, GameplaySet};
 use crate::{Godmode, player};
 use bevy::prelude::*;
-use bevy::window::Window;
-use rand::prelude::*;
 
+// Defaults, the values in play come from `GameTuning`
 pub const BULLET_SPEED: f32 = 500.0;
 pub const BULLET_LIFETIME: f32 = 2.0;
 
@@ -19,43 +29,19 @@ pub struct BulletVelocity(pub Vec2);
 #[derive(Component)]
 pub struct BulletLifetime(pub Timer);
 
-pub fn spawn_bullet(
-    mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Query<&Gamepad>,
-    player_query: Query<&Transform, With<player::Player>>,
-) {
-    let mut shoot = keyboard_input.just_pressed(KeyCode::Space);
-
-    if !shoot {
-        if let Some(gamepad) = gamepads.iter().next() {
-            if gamepad.just_pressed(GamepadButton::South) {
-                shoot = true;
-            }
-        }
-    }
+// What a bullet that flies on has already hit, including the fragments of what it broke, so it
+// passes through them instead of hitting them again. It also means the shot doesn't count as a
+// miss when the bullet is gone
+#[derive(Component, Debug, Default)]
+pub struct HitEntities(pub Vec<Entity>);
 
-    if shoot {
-        let Ok(player_transform) = player_query.single() else {
-            return;
-        }; // Safely get player transform
-        let bullet_direction = player_transform.rotation * Vec3::Y;
-        let bullet_position = player_transform.translation + bullet_direction * 20.0;
+// Hit points a bullet takes off what it hits
+#[derive(Component)]
+pub struct Damage(pub u32);
 
-        commands.spawn((
-            Sprite {
-                color: Color::srgb(1.0, 0.5, 0.0),
-                custom_size: Some(Vec2::new(10.0, 10.0)),
-                ..default()
-            },
-            Transform::from_translation(bullet_position),
-            GlobalTransform::default(),
-            Bullet,
-            BulletVelocity(bullet_direction.truncate() * BULLET_SPEED),
-            BulletLifetime(Timer::from_seconds(BULLET_LIFETIME, TimerMode::Once)),
-        ));
-    }
-}
+// Hits asteroids and saucers can take. They are destroyed when it reaches zero
+#[derive(Component)]
+pub struct Health(pub u32);
 
 pub fn move_bullets(
     mut bullet_query: Query<(&mut Transform, &BulletVelocity), With<Bullet>>,
@@ -67,173 +53,234 @@ pub fn move_bullets(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn despawn_bullets(
     mut commands: Commands,
-    mut bullet_query: Query<(Entity, &mut BulletLifetime), With<Bullet>>,
+    mut bullet_query: Query<
+        (
+            Entity,
+            &mut BulletLifetime,
+            Has<SaucerBullet>,
+            Has<HitEntities>,
+        ),
+        With<Bullet>,
+    >,
+    mut combo: ResMut<Combo>,
     time: Res<Time>,
 ) {
-    for (entity, mut lifetime) in bullet_query.iter_mut() {
+    for (entity, mut lifetime, from_saucer, hit) in bullet_query.iter_mut() {
         lifetime.0.tick(time.delta());
         if lifetime.0.finished() {
+            // The shot missed
+            if !from_saucer && !hit {
+                combo.break_streak();
+            }
             commands.entity(entity).despawn();
         }
     }
 }
 
-pub fn wrap_around_screen(
-    mut query: Query<&mut Transform, Or<(With<player::Player>, With<Asteroid>)>>,
-    windows: Query<&Window>,
+#[allow(clippy::type_complexity)]
+pub fn detect_bullet_asteroid_hits(
+    bullet_query: Query<(Entity, &Transform, &Collider, Option<&HitEntities>), With<Bullet>>,
+    asteroid_query: Query<(&Transform, &Collider), With<Asteroid>>,
+    mut contacts: Contacts,
+    mut hits: EventWriter<BulletHitAsteroid>,
 ) {
-    let Ok(_window) = windows.single() else {
-        return;
-    }; // Prefix with _
-    let half_width = _window.width() / 2.0;
-    let half_height = _window.height() / 2.0;
+    let mut nearby = Vec::new();
 
-    for mut transform in query.iter_mut() {
-        let mut translation = transform.translation;
-
-        if translation.x > half_width {
-            translation.x = -half_width;
-        } else if translation.x < -half_width {
-            translation.x = half_width;
+    for (bullet, bullet_transform, bullet_collider, hit_entities) in bullet_query.iter() {
+        if contacts.is_consumed(bullet) {
+            continue;
         }
-
-        if translation.y > half_height {
-            translation.y = -half_height;
-        } else if translation.y < -half_height {
-            translation.y = half_height;
+        contacts.nearby(
+            bullet_transform,
+            bullet_collider,
+            EdgeKind::Asteroid,
+            &mut nearby,
+        );
+        if let Some(hit_entities) = hit_entities {
+            nearby.retain(|asteroid| !hit_entities.0.contains(asteroid));
         }
-
-        transform.translation = translation;
-    }
-}
-
-pub fn despawn_out_of_bounds_bullets(
-    mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
-    windows: Query<&Window>,
-) {
-    let Ok(_window) = windows.single() else {
-        return;
-    }; // Prefix with _
-    let half_width = _window.width() / 2.0;
-    let half_height = _window.height() / 2.0;
-
-    for (entity, transform) in bullet_query.iter() {
-        let translation = transform.translation;
-
-        if translation.x > half_width
-            || translation.x < -half_width
-            || translation.y > half_height
-            || translation.y < -half_height
-        {
-            commands.entity(entity).despawn();
+        for &asteroid in &nearby {
+            let Ok((asteroid_transform, asteroid_collider)) = asteroid_query.get(asteroid) else {
+                continue;
+            };
+            if contacts.touching(
+                bullet_transform,
+                bullet_collider,
+                asteroid_transform,
+                asteroid_collider,
+                EdgeKind::Asteroid,
+            ) {
+                contacts.consume([bullet, asteroid]);
+                hits.write(BulletHitAsteroid { bullet, asteroid });
+                break;
+            }
         }
     }
 }
 
-fn bullet_asteroid_collision(
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn bullet_hits_asteroid(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
-    asteroid_query: Query<(Entity, &Transform, &AsteroidSize), With<Asteroid>>,
-    windows: Query<&Window>,
+    mut hits: EventReader<BulletHitAsteroid>,
+    mut bullet_query: Query<
+        (
+            &Damage,
+            &BulletVelocity,
+            Has<SaucerBullet>,
+            Option<&mut Piercing>,
+            Option<&mut HitEntities>,
+        ),
+        With<Bullet>,
+    >,
+    mut asteroid_query: Query<
+        (&Transform, &AsteroidVelocity, &AsteroidSize, &mut Health),
+        With<Asteroid>,
+    >,
     assets: Res<GameAssets>,
-    mut score: ResMut<Score>,
+    theme: Res<AsteroidTheme>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
 ) {
-    let Ok(_window) = windows.single() else {
-        return;
-    }; // Prefix with _
-    let mut rng = rand::thread_rng();
+    for hit in hits.read() {
+        let Ok((damage, bullet_velocity, from_saucer, piercing, hit_entities)) =
+            bullet_query.get_mut(hit.bullet)
+        else {
+            continue;
+        };
+        let Ok((asteroid_transform, asteroid_velocity, asteroid_size, mut health)) =
+            asteroid_query.get_mut(hit.asteroid)
+        else {
+            continue;
+        };
 
-    for (bullet_entity, bullet_transform) in bullet_query.iter() {
-        for (asteroid_entity, asteroid_transform, asteroid_size) in asteroid_query.iter() {
-            // Simple AABB collision detection for now
-            let bullet_size = 10.0; // Assuming bullet size is 10x10
-            let asteroid_current_size = match asteroid_size {
-                AsteroidSize::Large => 80.0,
-                AsteroidSize::Medium => 40.0,
-                AsteroidSize::Small => 20.0,
-            };
+        // Piercing bullets fly on until they run out of hits
+        let pierces = match piercing {
+            Some(mut piercing) if piercing.0 > 0 => {
+                piercing.0 -= 1;
+                true
+            }
+            _ => {
+                commands.entity(hit.bullet).despawn();
+                false
+            }
+        };
+        let mut passed_through = vec![hit.asteroid];
 
-            let distance = bullet_transform
-                .translation
-                .distance(asteroid_transform.translation);
-            if distance < (bullet_size / 2.0 + asteroid_current_size / 2.0) {
-                // Collision detected!
-                commands.entity(bullet_entity).despawn();
-                commands.entity(asteroid_entity).despawn();
+        // Tougher asteroids take more than one hit
+        health.0 = health.0.saturating_sub(damage.0);
+        if health.0 == 0 {
+            commands.entity(hit.asteroid).despawn();
+            commands.trigger(AsteroidDestroyed {
+                size: *asteroid_size,
+                position: asteroid_transform.translation,
+                by: if from_saucer {
+                    Shooter::Saucer
+                } else {
+                    Shooter::Player
+                },
+            });
+
+            // Saucers shoot asteroids too, but only the player gets power-ups
+            if !from_saucer {
+                powerup::maybe_drop(
+                    &mut commands,
+                    &mut rng,
+                    &tuning.power_up,
+                    asteroid_transform.translation,
+                );
+            }
+            passed_through.extend(split_asteroid(
+                &mut commands,
+                *asteroid_size,
+                asteroid_transform.translation,
+                asteroid_velocity.0,
+                bullet_velocity.0,
+                &mut rng,
+                &tuning.asteroid,
+                AsteroidLook {
+                    theme: *theme,
+                    sprite: &assets.asteroid,
+                },
+            ));
+        }
 
-                match asteroid_size {
-                    AsteroidSize::Large => {
-                        score.0 += 20;
-                        for _ in 0..2 {
-                            let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
-                            let speed = ASTEROID_MEDIUM_SPEED;
-                            let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
-                            spawn_asteroid(
-                                &mut commands,
-                                AsteroidSize::Medium,
-                                asteroid_transform.translation,
-                                velocity,
-                                &assets.asteroid,
-                            );
-                        }
-                    }
-                    AsteroidSize::Medium => {
-                        score.0 += 50;
-                        for _ in 0..2 {
-                            let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
-                            let speed = ASTEROID_SMALL_SPEED;
-                            let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
-                            spawn_asteroid(
-                                &mut commands,
-                                AsteroidSize::Small,
-                                asteroid_transform.translation,
-                                velocity,
-                                &assets.asteroid,
-                            );
-                        }
-                    }
-                    AsteroidSize::Small => {
-                        // Small asteroids just disappear
-                        score.0 += 100;
-                    }
+        if pierces {
+            match hit_entities {
+                Some(mut hit_entities) => hit_entities.0.extend(passed_through),
+                None => {
+                    commands
+                        .entity(hit.bullet)
+                        .insert(HitEntities(passed_through));
                 }
             }
         }
     }
 }
 
-fn player_asteroid_collision(
-    mut commands: Commands,
-    player_query: Query<(Entity, &Transform), With<player::Player>>,
-    asteroid_query: Query<(&Transform, &AsteroidSize), With<Asteroid>>,
-    mut game_state: ResMut<NextState<GameState>>,
-    godmode: Res<Godmode>,
+#[allow(clippy::type_complexity)]
+pub fn detect_player_asteroid_hits(
+    player_query: Query<
+        (Entity, &Transform, &Collider),
+        (
+            With<player::Player>,
+            Without<player::Invulnerable>,
+            Without<HyperspaceJump>,
+        ),
+    >,
+    asteroid_query: Query<(&Transform, &Collider), With<Asteroid>>,
+    mut contacts: Contacts,
+    mut hits: EventWriter<PlayerHitAsteroid>,
 ) {
-    let Ok((player_entity, player_transform)) = player_query.single() else {
+    let Ok((player, player_transform, player_collider)) = player_query.single() else {
         return;
     };
+    if contacts.is_consumed(player) {
+        return;
+    }
 
-    let player_size = 50.0; // Assuming player size is 50x50
-
-    for (asteroid_transform, asteroid_size) in asteroid_query.iter() {
-        let asteroid_current_size = match asteroid_size {
-            AsteroidSize::Large => 80.0,
-            AsteroidSize::Medium => 40.0,
-            AsteroidSize::Small => 20.0,
+    let mut nearby = Vec::new();
+    contacts.nearby(
+        player_transform,
+        player_collider,
+        EdgeKind::Asteroid,
+        &mut nearby,
+    );
+    for &asteroid in &nearby {
+        let Ok((asteroid_transform, asteroid_collider)) = asteroid_query.get(asteroid) else {
+            continue;
         };
+        if contacts.touching(
+            player_transform,
+            player_collider,
+            asteroid_transform,
+            asteroid_collider,
+            EdgeKind::Asteroid,
+        ) {
+            contacts.consume([player, asteroid]);
+            hits.write(PlayerHitAsteroid { player, asteroid });
+            break;
+        }
+    }
+}
 
-        let distance = player_transform
-            .translation
-            .distance(asteroid_transform.translation);
-        if distance < (player_size / 2.0 + asteroid_current_size / 2.0) && !godmode.0 {
-            // Collision detected! Game Over
-            println!("Game Over! Player hit an asteroid.");
-            commands.entity(player_entity).despawn();
-            game_state.set(GameState::GameOver);
+// The asteroid is left alone, only the ship is lost. A shielded ship survives it
+pub fn player_hits_asteroid(
+    mut commands: Commands,
+    mut hits: EventReader<PlayerHitAsteroid>,
+    player_query: Query<&ActivePowerUps, With<player::Player>>,
+    godmode: Res<Godmode>,
+) {
+    for hit in hits.read() {
+        let Ok(power_ups) = player_query.get(hit.player) else {
+            continue;
+        };
+        if power_ups.has(PowerUpKind::Shield) || godmode.0 {
+            continue;
         }
+        commands.trigger_targets(ShipDestroyed(ShipLoss::Asteroid), hit.player);
     }
 }
 
@@ -242,17 +289,117 @@ pub struct MechanicsPlugin;
 impl Plugin for MechanicsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                spawn_bullet,
-                move_bullets,
-                despawn_bullets,
-                wrap_around_screen,
-                despawn_out_of_bounds_bullets,
-                bullet_asteroid_collision,
-                player_asteroid_collision,
-            )
-                .run_if(in_state(GameState::Playing)),
+                (move_bullets, despawn_bullets)
+                    .chain()
+                    .in_set(GameplaySet::Movement),
+                (
+                    detect_bullet_asteroid_hits,
+                    bullet_hits_asteroid,
+                    detect_player_asteroid_hits,
+                    player_hits_asteroid,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Collision),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::{CollisionPlugin, Consumed};
+    use crate::spatial::SpatialHashPlugin;
+    use crate::{PlayField, asteroid::AsteroidTheme};
+
+    // The asteroid collision systems, run once per update, with a piercing bullet sitting on an
+    // asteroid of `size` with `hit_points`
+    fn piercing_bullet_on_asteroid(size: AsteroidSize, hit_points: u32) -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.add_plugins((CollisionPlugin, SpatialHashPlugin))
+            .insert_resource(PlayField::new(1024.0, 768.0))
+            .insert_resource(GameRng::new(7))
+            .init_resource::<GameTuning>()
+            .init_resource::<GameAssets>()
+            .init_resource::<AsteroidTheme>()
+            .add_systems(
+                Update,
+                (
+                    |mut consumed: ResMut<Consumed>| *consumed = Consumed::default(),
+                    detect_bullet_asteroid_hits,
+                    bullet_hits_asteroid,
+                )
+                    .chain(),
+            );
+        let asteroid = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Asteroid,
+                size,
+                AsteroidVelocity(Vec2::ZERO),
+                Health(hit_points),
+                Collider::circle(20.0),
+            ))
+            .id();
+        let bullet = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Bullet,
+                BulletVelocity(Vec2::new(BULLET_SPEED, 0.0)),
+                Damage(1),
+                Piercing(3),
+                Collider::circle(5.0),
+            ))
+            .id();
+        (app, asteroid, bullet)
+    }
+
+    #[test]
+    fn piercing_bullet_passes_through_an_asteroid_once() {
+        let (mut app, asteroid, bullet) = piercing_bullet_on_asteroid(AsteroidSize::Medium, 2);
+        // The bullet overlaps the asteroid for several ticks while it passes through
+        for _ in 0..5 {
+            app.update();
+        }
+        let world = app.world();
+        assert_eq!(
+            world.get::<Health>(asteroid).map(|health| health.0),
+            Some(1)
+        );
+        assert_eq!(
+            world.get::<Piercing>(bullet).map(|piercing| piercing.0),
+            Some(2)
+        );
+        assert_eq!(
+            world.get::<HitEntities>(bullet).map(|hit| hit.0.clone()),
+            Some(vec![asteroid])
+        );
+    }
+
+    #[test]
+    fn piercing_bullet_passes_through_the_fragments_of_what_it_broke() {
+        let (mut app, asteroid, bullet) = piercing_bullet_on_asteroid(AsteroidSize::Medium, 1);
+        for _ in 0..5 {
+            app.update();
+        }
+        let world = app.world_mut();
+        assert!(world.get_entity(asteroid).is_err());
+        assert_eq!(
+            world.get::<Piercing>(bullet).map(|piercing| piercing.0),
+            Some(2)
+        );
+        let fragments = world
+            .query_filtered::<Entity, With<Asteroid>>()
+            .iter(world)
+            .count();
+        assert_eq!(
+            fragments,
+            GameTuning::default().asteroid.medium_fragments.len()
         );
+        assert!(fragments > 0);
     }
 }
@@ -4,7 +4,9 @@ use bevy::{
     prelude::*,
 };
 
-use super::{GameState, Godmode, Volume, despawn_screen};
+use super::asteroid::AsteroidTheme;
+use super::highscores::HighScores;
+use super::{GameState, Godmode, InertialDampeners, StartRun, Volume, despawn_screen};
 
 pub fn menu_plugin(app: &mut App) {
     app
@@ -13,6 +15,14 @@ pub fn menu_plugin(app: &mut App) {
         // Current screen in the menu is handled by an independent state from `GameState`
         .init_state::<MenuState>()
         .add_systems(OnEnter(GameState::Menu), menu_setup)
+        // The pause menu is shown on top of the frozen game
+        .add_systems(OnEnter(GameState::Paused), pause_setup)
+        .add_systems(OnExit(GameState::Paused), pause_cleanup)
+        .add_systems(OnEnter(MenuState::Pause), pause_menu_setup)
+        .add_systems(
+            OnExit(MenuState::Pause),
+            despawn_screen::<OnPauseMenuScreen>,
+        )
         // Systems to handle the main menu screen
         .add_systems(OnEnter(MenuState::Main), main_menu_setup)
         .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
@@ -22,6 +32,12 @@ pub fn menu_plugin(app: &mut App) {
             OnExit(MenuState::Settings),
             despawn_screen::<OnSettingsMenuScreen>,
         )
+        // Systems to handle the high scores screen
+        .add_systems(OnEnter(MenuState::HighScores), high_scores_menu_setup)
+        .add_systems(
+            OnExit(MenuState::HighScores),
+            despawn_screen::<OnHighScoresMenuScreen>,
+        )
         // Systems to handle the sound settings screen
         .add_systems(OnEnter(MenuState::SettingsSound), sound_settings_menu_setup)
         .add_systems(
@@ -35,7 +51,9 @@ pub fn menu_plugin(app: &mut App) {
         // Common systems to all screens that handles buttons behavior
         .add_systems(
             Update,
-            (menu_action, button_system).run_if(in_state(GameState::Menu)),
+            (menu_navigation, menu_action, button_system)
+                .chain()
+                .run_if(in_state(GameState::Menu).or(in_state(GameState::Paused))),
         );
 }
 
@@ -43,6 +61,8 @@ pub fn menu_plugin(app: &mut App) {
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 enum MenuState {
     Main,
+    HighScores,
+    Pause,
     Settings,
     SettingsSound,
     #[default]
@@ -53,6 +73,14 @@ enum MenuState {
 #[derive(Component)]
 struct OnMainMenuScreen;
 
+// Tag component used to tag entities added on the high scores screen
+#[derive(Component)]
+struct OnHighScoresMenuScreen;
+
+// Tag component used to tag entities added on the pause menu screen
+#[derive(Component)]
+struct OnPauseMenuScreen;
+
 // Tag component used to tag entities added on the settings menu screen
 #[derive(Component)]
 struct OnSettingsMenuScreen;
@@ -65,24 +93,54 @@ const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const HOVERED_PRESSED_BUTTON: Color = Color::srgb(0.25, 0.65, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const FOCUSED_OUTLINE: Color = Color::srgb(0.9, 0.9, 0.9);
+const PAUSE_OVERLAY: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
 
 // Tag component used to mark which setting is currently selected
 #[derive(Component)]
 struct SelectedOption;
 
+// Tag component used to mark the button that keyboard and gamepad input acts on
+#[derive(Component)]
+struct MenuFocus;
+
+// Tag component used to mark a button pressed with the keyboard or gamepad, to release it on
+// the next frame
+#[derive(Component)]
+struct KeyPressed;
+
 // All actions that can be triggered from a button click
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    HighScores,
     Settings,
     Godmode,
+    InertialDampeners,
+    AsteroidTheme,
     SettingsSound,
     BackToMainMenu,
     BackToSettings,
+    Resume,
+    Restart,
+    QuitToMenu,
     Quit,
 }
 
+impl MenuButtonAction {
+    // Whether this button leaves the current screen, so Escape can press it
+    fn is_back(&self) -> bool {
+        matches!(
+            self,
+            MenuButtonAction::BackToMainMenu
+                | MenuButtonAction::BackToSettings
+                | MenuButtonAction::Resume
+        )
+    }
+}
+
 // This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
@@ -101,6 +159,7 @@ fn button_system(
 
 // This system updates the settings when a new value for a setting is selected, and marks
 // the button as the one currently selected
+#[allow(clippy::type_complexity)]
 fn setting_button<T: Resource + Component + PartialEq + Copy>(
     interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
     selected_query: Single<(Entity, &mut BackgroundColor), With<SelectedOption>>,
@@ -122,6 +181,191 @@ fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::Main);
 }
 
+fn pause_setup(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Pause);
+}
+
+fn pause_cleanup(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Disabled);
+}
+
+// Lets the menus be used without a mouse. Up and down (or left and right) move the focus
+// between the buttons of the screen, Enter, Space or the gamepad South button press the
+// focused one, and Escape or the gamepad East button press the screen's back button.
+// Pressing works by setting the `Interaction`, so it goes through the same systems as a click
+#[allow(clippy::type_complexity)]
+fn menu_navigation(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut buttons: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Interaction,
+            Option<&MenuButtonAction>,
+            Has<MenuFocus>,
+            Has<KeyPressed>,
+        ),
+        With<Button>,
+    >,
+) {
+    // The UI only releases presses made with the mouse
+    for (entity, _, mut interaction, _, _, key_pressed) in &mut buttons {
+        if key_pressed {
+            *interaction = Interaction::None;
+            commands.entity(entity).remove::<KeyPressed>();
+        }
+    }
+
+    let gamepad = gamepads.iter().next();
+    let pressed = |keys: &[KeyCode], gamepad_buttons: &[GamepadButton]| {
+        keyboard_input.any_just_pressed(keys.iter().copied())
+            || gamepad
+                .is_some_and(|gamepad| gamepad.any_just_pressed(gamepad_buttons.iter().copied()))
+    };
+    let previous = pressed(
+        &[
+            KeyCode::ArrowUp,
+            KeyCode::ArrowLeft,
+            KeyCode::KeyW,
+            KeyCode::KeyA,
+        ],
+        &[GamepadButton::DPadUp, GamepadButton::DPadLeft],
+    );
+    let next = pressed(
+        &[
+            KeyCode::ArrowDown,
+            KeyCode::ArrowRight,
+            KeyCode::KeyS,
+            KeyCode::KeyD,
+            KeyCode::Tab,
+        ],
+        &[GamepadButton::DPadDown, GamepadButton::DPadRight],
+    );
+    let activate = pressed(&[KeyCode::Enter, KeyCode::Space], &[GamepadButton::South]);
+    let back = pressed(
+        &[KeyCode::Escape, KeyCode::Backspace],
+        &[GamepadButton::East, GamepadButton::Start],
+    );
+
+    // Buttons in reading order, top to bottom then left to right
+    let mut order: Vec<(Entity, Vec3, bool)> = buttons
+        .iter()
+        .map(|(entity, transform, _, _, focused, _)| (entity, transform.translation(), focused))
+        .collect();
+    order.sort_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let focused = order.iter().position(|(_, _, focused)| *focused);
+
+    let to_press = if back {
+        buttons
+            .iter()
+            .find(|(_, _, _, action, ..)| action.is_some_and(MenuButtonAction::is_back))
+            .map(|(entity, ..)| entity)
+    } else if activate {
+        focused.map(|index| order[index].0)
+    } else {
+        None
+    };
+    if let Some(entity) = to_press
+        && let Ok((_, _, mut interaction, ..)) = buttons.get_mut(entity)
+    {
+        *interaction = Interaction::Pressed;
+        commands.entity(entity).insert(KeyPressed);
+        return;
+    }
+
+    if order.is_empty() || !(previous || next) {
+        return;
+    }
+    let target = match focused {
+        // The first key press only shows where the focus is
+        None => 0,
+        Some(index) if next => (index + 1) % order.len(),
+        Some(index) => (index + order.len() - 1) % order.len(),
+    };
+    if let Some(index) = focused {
+        commands
+            .entity(order[index].0)
+            .remove::<(MenuFocus, Outline)>();
+    }
+    commands.entity(order[target].0).insert((
+        MenuFocus,
+        Outline::new(Val::Px(3.0), Val::ZERO, FOCUSED_OUTLINE),
+    ));
+}
+
+fn pause_menu_setup(mut commands: Commands) {
+    let button_node = Node {
+        width: Val::Px(300.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font_size: 33.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    );
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        // Dim the game behind the menu
+        BackgroundColor(PAUSE_OVERLAY),
+        OnPauseMenuScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            Children::spawn((
+                Spawn((
+                    Text::new("Paused"),
+                    TextFont {
+                        font_size: 67.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    Node {
+                        margin: UiRect::all(Val::Px(30.0)),
+                        ..default()
+                    },
+                )),
+                SpawnIter(
+                    [
+                        (MenuButtonAction::Resume, "Resume"),
+                        (MenuButtonAction::Restart, "Restart"),
+                        (MenuButtonAction::Settings, "Settings"),
+                        (MenuButtonAction::QuitToMenu, "Quit to Menu"),
+                    ]
+                    .into_iter()
+                    .map(move |(action, text)| {
+                        (
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            action,
+                            children![(Text::new(text), button_text_style.clone())],
+                        )
+                    })
+                ),
+            ))
+        )],
+    ));
+}
+
 fn main_menu_setup(mut commands: Commands) {
     // Common style for all buttons on the screen
     let button_node = Node {
@@ -166,8 +410,9 @@ fn main_menu_setup(mut commands: Commands) {
                         ..default()
                     },
                 ),
-                // Display three buttons for each action available from the main menu:
+                // Display four buttons for each action available from the main menu:
                 // - new game
+                // - high scores
                 // - settings
                 // - quit
                 (
@@ -181,6 +426,17 @@ fn main_menu_setup(mut commands: Commands) {
                         TextColor(Color::WHITE),
                     ),]
                 ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::HighScores,
+                    children![(
+                        Text::new("High Scores"),
+                        button_text_font.clone(),
+                        TextColor(Color::WHITE),
+                    ),]
+                ),
                 (
                     Button,
                     button_node.clone(),
@@ -204,7 +460,119 @@ fn main_menu_setup(mut commands: Commands) {
     ));
 }
 
-fn settings_menu_setup(mut commands: Commands) {
+fn high_scores_menu_setup(mut commands: Commands, high_scores: Res<HighScores>) {
+    let button_node = Node {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font_size: 33.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    );
+    let row_text_font = TextFont {
+        font_size: 22.0,
+        ..default()
+    };
+
+    // One row per run, with a fixed width for each column so they line up
+    let columns = [40.0, 70.0, 100.0, 130.0, 70.0, 220.0];
+    let header = ["#", "Name", "Score", "Date", "Time", "Seed"].map(str::to_string);
+    let rows: Vec<[String; 6]> = high_scores
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            [
+                format!("{}.", index + 1),
+                entry.initials.clone(),
+                entry.score.to_string(),
+                entry.date_string(),
+                entry.duration_string(),
+                entry.seed.to_string(),
+            ]
+        })
+        .collect();
+    let empty = rows.is_empty();
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        OnHighScoresMenuScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            Children::spawn((
+                Spawn((Text::new("High Scores"), button_text_style.clone())),
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    if empty {
+                        parent.spawn((
+                            Text::new("No runs yet"),
+                            row_text_font,
+                            TextColor(Color::WHITE),
+                            Node {
+                                margin: UiRect::all(Val::Px(20.0)),
+                                ..default()
+                            },
+                        ));
+                        return;
+                    }
+                    for (index, row) in std::iter::once(header).chain(rows).enumerate() {
+                        // The header is dimmed
+                        let color = if index == 0 {
+                            Color::srgb(0.6, 0.6, 0.6)
+                        } else {
+                            Color::WHITE
+                        };
+                        parent
+                            .spawn(Node {
+                                margin: UiRect::top(Val::Px(6.0)),
+                                ..default()
+                            })
+                            .with_children(|row_parent| {
+                                for (width, cell) in columns.iter().zip(row) {
+                                    row_parent.spawn((
+                                        Text::new(cell),
+                                        row_text_font.clone(),
+                                        TextColor(color),
+                                        Node {
+                                            width: Val::Px(*width),
+                                            ..default()
+                                        },
+                                    ));
+                                }
+                            });
+                    }
+                }),
+                Spawn((
+                    Button,
+                    button_node,
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::BackToMainMenu,
+                    children![(Text::new("Back"), button_text_style)]
+                )),
+            ))
+        )],
+    ));
+}
+
+fn settings_menu_setup(mut commands: Commands, game_state: Res<State<GameState>>) {
     let button_node = Node {
         width: Val::Px(200.0),
         height: Val::Px(65.0),
@@ -221,6 +589,7 @@ fn settings_menu_setup(mut commands: Commands) {
         },
         TextColor(Color::WHITE),
     );
+    let paused = *game_state.get() == GameState::Paused;
 
     commands.spawn((
         Node {
@@ -230,6 +599,8 @@ fn settings_menu_setup(mut commands: Commands) {
             justify_content: JustifyContent::Center,
             ..default()
         },
+        // Keep the game dimmed when the settings are opened from the pause menu
+        BackgroundColor(if paused { PAUSE_OVERLAY } else { Color::NONE }),
         OnSettingsMenuScreen,
         children![(
             Node {
@@ -241,10 +612,23 @@ fn settings_menu_setup(mut commands: Commands) {
             Children::spawn(SpawnIter(
                 [
                     (MenuButtonAction::Godmode, "Godmode"),
+                    (MenuButtonAction::InertialDampeners, "Dampeners"),
+                    (MenuButtonAction::AsteroidTheme, "Asteroids"),
                     (MenuButtonAction::SettingsSound, "Sound"),
                     (MenuButtonAction::BackToMainMenu, "Back"),
                 ]
                 .into_iter()
+                // Replays store the godmode, dampeners and asteroid theme a run was played
+                // with, so they can't change mid-run
+                .filter(move |(action, _)| {
+                    !(paused
+                        && matches!(
+                            action,
+                            MenuButtonAction::Godmode
+                                | MenuButtonAction::InertialDampeners
+                                | MenuButtonAction::AsteroidTheme
+                        ))
+                })
                 .map(move |(action, text)| {
                     (
                         Button,
@@ -334,16 +718,22 @@ fn sound_settings_menu_setup(mut commands: Commands, volume: Res<Volume>) {
     ));
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn menu_action(
+    mut commands: Commands,
     interaction_query: Query<
         (&Interaction, &MenuButtonAction),
         (Changed<Interaction>, With<Button>),
     >,
     mut app_exit_events: EventWriter<AppExit>,
     mut menu_state: ResMut<NextState<MenuState>>,
+    current_game_state: Res<State<GameState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut godmode: ResMut<Godmode>,
+    mut dampeners: ResMut<InertialDampeners>,
+    mut asteroid_theme: ResMut<AsteroidTheme>,
 ) {
+    let paused = *current_game_state.get() == GameState::Paused;
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed {
             match menu_button_action {
@@ -354,19 +744,42 @@ fn menu_action(
                     game_state.set(GameState::Loading);
                     menu_state.set(MenuState::Disabled);
                 }
+                MenuButtonAction::HighScores => menu_state.set(MenuState::HighScores),
                 MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
                 MenuButtonAction::Godmode => {
                     //ResMut<Godmode> = true;
                     godmode.0 = !godmode.0;
                     println!("Godmode toggled. Current state: {}", godmode.0);
                 }
+                MenuButtonAction::InertialDampeners => {
+                    dampeners.0 = !dampeners.0;
+                    info!(
+                        "Inertial dampeners {}",
+                        if dampeners.0 { "on" } else { "off" }
+                    );
+                }
+                MenuButtonAction::AsteroidTheme => {
+                    *asteroid_theme = match *asteroid_theme {
+                        AsteroidTheme::Polygons => AsteroidTheme::Sprites,
+                        AsteroidTheme::Sprites => AsteroidTheme::Polygons,
+                    };
+                    info!("Asteroid theme {:?}", *asteroid_theme);
+                }
                 MenuButtonAction::SettingsSound => {
                     menu_state.set(MenuState::SettingsSound);
                 }
+                // The settings lead back to the pause menu when they were opened from it
+                MenuButtonAction::BackToMainMenu if paused => menu_state.set(MenuState::Pause),
                 MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
                 MenuButtonAction::BackToSettings => {
                     menu_state.set(MenuState::Settings);
                 }
+                MenuButtonAction::Resume => game_state.set(GameState::Playing),
+                MenuButtonAction::Restart => {
+                    commands.trigger(StartRun);
+                    game_state.set(GameState::Playing);
+                }
+                MenuButtonAction::QuitToMenu => game_state.set(GameState::Menu),
             }
         }
     }
@@ -0,0 +1,40 @@
+use bevy::{input::gamepad::GamepadConnectionEvent, prelude::*, window::WindowFocused};
+
+use crate::GameState;
+use crate::input::PlayerInput;
+
+// Pauses the game on Escape or the gamepad Start button, and on its own when the window loses
+// focus or a gamepad disconnects. The overlay menu is one of the menu screens, see
+// `menu::pause_menu_setup`. Nothing in `GameplaySet` runs while paused, so pausing doesn't
+// change how a run plays out
+pub fn pause_plugin(app: &mut App) {
+    app.add_systems(Update, pause_game.run_if(in_state(GameState::Playing)))
+        .add_systems(OnExit(GameState::Paused), clear_player_input);
+}
+
+fn pause_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let pressed = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Start));
+    let focus_lost = focus_events.read().any(|event| !event.focused);
+    let gamepad_lost = connection_events.read().any(|event| event.disconnected());
+
+    if pressed || focus_lost || gamepad_lost {
+        if gamepad_lost {
+            info!("Gamepad disconnected, pausing");
+        }
+        game_state.set(GameState::Paused);
+    }
+}
+
+// The button that picked a menu entry shouldn't also fire a shot when the game resumes
+fn clear_player_input(mut input: ResMut<PlayerInput>) {
+    *input = PlayerInput::default();
+}
@@ -1,8 +1,46 @@
-use crate::GameState;
+use crate::collider::Collider;
+use crate::events::{GameOverReached, PlayerDied, PlayerSpawned};
+use crate::hyperspace::HyperspaceJump;
+use crate::input::PlayerInput;
+use crate::powerup::ActivePowerUps;
+use crate::timestep::InterpolatedTransform;
+use crate::tuning::GameTuning;
+use crate::weapon::Weapon;
+use crate::{GameAssets, GameState, GameplaySet, InertialDampeners, Lives};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+// Defaults, the values in play come from `GameTuning`
 pub const PLAYER_ROTATION_SPEED: f32 = 2.5;
 pub const PLAYER_THRUST_FORCE: f32 = 100.0;
+pub const PLAYER_LIVES: u32 = 3;
+pub const PLAYER_RESPAWN_DELAY: f32 = 2.0;
+pub const PLAYER_INVULNERABILITY: f32 = 3.0;
+pub const PLAYER_DRAG: f32 = 0.3;
+pub const PLAYER_MAX_SPEED: f32 = 300.0;
+pub const PLAYER_ANGULAR_ACCELERATION: f32 = 20.0;
+pub const PLAYER_REVERSE_FORCE: f32 = 100.0;
+pub const PLAYER_DAMPENING: f32 = 2.5;
+
+pub const PLAYER_COLOR: Color = Color::srgb(0.7, 0.7, 0.8);
+
+// Width and height of the ship's sprite
+const PLAYER_SIZE: f32 = 75.0;
+
+// Corners of the ship's hull, nose first, for a sprite of size 1
+const PLAYER_OUTLINE: [Vec2; 5] = [
+    Vec2::new(0.0, 0.385),
+    Vec2::new(0.265, -0.25),
+    Vec2::new(0.12, -0.395),
+    Vec2::new(-0.12, -0.395),
+    Vec2::new(-0.265, -0.25),
+];
+
+// The ship's circle without `collision.outlines`, well inside the sprite
+const PLAYER_HIT_RADIUS: f32 = 25.0;
+
+// Times per second an invulnerable ship blinks
+const BLINK_RATE: f32 = 8.0;
 
 #[derive(Component)]
 pub struct Player;
@@ -10,90 +48,255 @@ pub struct Player;
 #[derive(Component, Default)]
 pub struct PlayerVelocity(pub Vec2);
 
-pub fn spawn_player(commands: &mut Commands, player_handle: &Handle<Image>) {
-    commands.spawn((
-        Sprite {
-            image: player_handle.clone(),
-            color: Color::srgb(0.7, 0.7, 0.8),
-            custom_size: Some(Vec2::new(75.0, 75.0)),
-            ..default()
-        },
-        Transform::from_xyz(0.0, 0.0, 0.0),
-        GlobalTransform::default(),
-        Player,
-        PlayerVelocity::default(),
-    ));
+// Radians per second the ship is turning, positive to the left
+#[derive(Component, Default)]
+pub struct PlayerSpin(pub f32);
+
+// What pulling back on the thrust does
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReverseThrust {
+    // Nothing, like the arcade original
+    Off,
+    // Pushes the ship backwards
+    Reverse,
+    // Slows the ship down, whichever way it's flying, until it stands still
+    #[default]
+    Brake,
 }
 
-pub fn player_movement(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Query<&Gamepad>,
-    mut player_query: Query<(&mut Transform, &mut PlayerVelocity), With<Player>>,
+// Asteroids pass through the ship until the timer runs out. It blinks meanwhile
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+// Counts down to the next ship after a lost life
+#[derive(Resource, Default)]
+pub struct PlayerRespawn(pub Option<Timer>);
+
+// What the ship was lost to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShipLoss {
+    Asteroid,
+    Malfunction,
+    Saucer,
+    // Flew off the edge of the play field
+    Edge,
+}
+
+// Triggered on the ship entity to destroy it. Costs a life, and with the last one the game
+#[derive(Event, Debug)]
+pub struct ShipDestroyed(pub ShipLoss);
+
+pub fn spawn_player(
+    commands: &mut Commands,
+    player_handle: &Handle<Image>,
+    tuning: &GameTuning,
+) -> Entity {
+    let transform = Transform::from_xyz(0.0, 0.0, 0.0);
+    let player = commands
+        .spawn((
+            Sprite {
+                image: player_handle.clone(),
+                color: PLAYER_COLOR,
+                custom_size: Some(Vec2::splat(PLAYER_SIZE)),
+                ..default()
+            },
+            transform,
+            GlobalTransform::default(),
+            InterpolatedTransform::default(),
+            Player,
+            PlayerVelocity::default(),
+            PlayerSpin::default(),
+            player_collider(tuning),
+            ActivePowerUps::default(),
+            Weapon::new(tuning),
+        ))
+        .id();
+    commands.trigger(PlayerSpawned {
+        position: transform.translation,
+    });
+    player
+}
+
+fn player_collider(tuning: &GameTuning) -> Collider {
+    if tuning.collision.outlines {
+        Collider::convex(
+            PLAYER_OUTLINE
+                .iter()
+                .map(|corner| *corner * PLAYER_SIZE)
+                .collect(),
+        )
+    } else {
+        Collider::circle(PLAYER_HIT_RADIUS)
+    }
+}
+
+pub fn respawn_player(
+    mut commands: Commands,
+    mut respawn: ResMut<PlayerRespawn>,
+    assets: Res<GameAssets>,
+    tuning: Res<GameTuning>,
     time: Res<Time>,
 ) {
-    let Some((mut player_transform, mut player_velocity)) = player_query.single_mut().ok() else {
+    let Some(timer) = respawn.0.as_mut() else {
         return;
     };
+    if timer.tick(time.delta()).finished() {
+        respawn.0 = None;
+        let player = spawn_player(&mut commands, &assets.player, &tuning);
+        commands
+            .entity(player)
+            .insert(Invulnerable(Timer::from_seconds(
+                tuning.player.invulnerability,
+                TimerMode::Once,
+            )));
+    }
+}
 
-    let mut rotation_input = 0.0;
-    let mut thrust_input = false;
-
-    // Keyboard
-    if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
-        rotation_input += 1.0;
+fn destroy_ship(
+    trigger: Trigger<ShipDestroyed>,
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut lives: ResMut<Lives>,
+    mut respawn: ResMut<PlayerRespawn>,
+    tuning: Res<GameTuning>,
+) {
+    // Hit by two things in the same tick, the first one already took the ship
+    let Ok(transform) = player_query.get(trigger.target()) else {
+        return;
+    };
+    let loss = trigger.event().0;
+    commands.entity(trigger.target()).despawn();
+    lives.0 = lives.0.saturating_sub(1);
+    commands.trigger(PlayerDied {
+        loss,
+        position: transform.translation,
+        lives_left: lives.0,
+    });
+    if lives.0 == 0 {
+        commands.trigger(GameOverReached { loss });
+        match loss {
+            ShipLoss::Asteroid => info!("Game Over! Player hit an asteroid."),
+            ShipLoss::Malfunction => info!("Game Over! Hyperspace malfunction."),
+            ShipLoss::Saucer => info!("Game Over! Player was shot down by a saucer."),
+            ShipLoss::Edge => info!("Game Over! Player flew off the edge."),
+        }
+        game_state.set(GameState::GameOver);
+    } else {
+        respawn.0 = Some(Timer::from_seconds(
+            tuning.player.respawn_delay,
+            TimerMode::Once,
+        ));
     }
-    if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-        rotation_input -= 1.0;
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable, mut visibility) in query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+        }
     }
-    if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
-        thrust_input = true;
+}
+
+// Only changes how the ship looks, so it runs every frame rather than every tick
+fn blink_invulnerable(mut query: Query<(&Invulnerable, &mut Visibility)>) {
+    for (invulnerable, mut visibility) in query.iter_mut() {
+        let phase = (invulnerable.0.elapsed_secs() * BLINK_RATE * 2.0) as u32;
+        visibility.set_if_neq(if phase.is_multiple_of(2) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
     }
+}
 
-    // Gamepad
-    if let Some(gamepad) = gamepads.iter().next() {
-        if gamepad.pressed(GamepadButton::DPadLeft) {
-            rotation_input += 1.0;
-        }
-        if gamepad.pressed(GamepadButton::DPadRight) {
-            rotation_input -= 1.0;
-        }
-        if gamepad.pressed(GamepadButton::DPadUp) {
-            thrust_input = true;
-        }
+// Turning speeds up and slows down at `angular_acceleration`, thrust pushes the ship along and
+// drag slows it down, never past `max_speed`. With the inertial dampeners on, the ship also
+// slows down quickly whenever it isn't thrusting
+#[allow(clippy::type_complexity)]
+pub fn player_movement(
+    input: Res<PlayerInput>,
+    mut player_query: Query<
+        (&mut Transform, &mut PlayerVelocity, &mut PlayerSpin),
+        (With<Player>, Without<HyperspaceJump>),
+    >,
+    dampeners: Res<InertialDampeners>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    let Some((mut player_transform, mut player_velocity, mut spin)) =
+        player_query.single_mut().ok()
+    else {
+        return;
+    };
+    let tuning = &tuning.player;
+    let delta = time.delta_secs();
 
-        if let Some(left_stick_x) = gamepad.get(GamepadAxis::LeftStickX) {
-            if left_stick_x.abs() > 0.1 {
-                rotation_input -= left_stick_x;
-            }
-        }
-        if let Some(left_stick_y) = gamepad.get(GamepadAxis::LeftStickY) {
-            if left_stick_y > 0.1 {
-                thrust_input = true;
+    // Rotation, right away when there's no angular acceleration
+    let target_spin = input.rotation * tuning.rotation_speed;
+    spin.0 = if tuning.angular_acceleration > 0.0 {
+        let step = tuning.angular_acceleration * delta;
+        spin.0 + (target_spin - spin.0).clamp(-step, step)
+    } else {
+        target_spin
+    };
+    if spin.0 != 0.0 {
+        player_transform.rotate_z(spin.0 * delta);
+    }
+
+    // Thrust
+    let forward = (player_transform.rotation * Vec3::Y).truncate();
+    if input.thrust > 0.0 {
+        player_velocity.0 += forward * tuning.thrust_force * input.thrust * delta;
+    } else if input.thrust < 0.0 {
+        let pull = -input.thrust * tuning.reverse_force * delta;
+        match tuning.reverse_thrust {
+            ReverseThrust::Off => {}
+            ReverseThrust::Reverse => player_velocity.0 -= forward * pull,
+            ReverseThrust::Brake => {
+                let speed = player_velocity.0.length();
+                player_velocity.0 = player_velocity.0.normalize_or_zero() * (speed - pull).max(0.0);
             }
         }
     }
 
-    // Rotation
-    if rotation_input != 0.0 {
-        player_transform
-            .rotate_z(rotation_input.clamp(-1.0, 1.0) * PLAYER_ROTATION_SPEED * time.delta_secs());
+    // Drag
+    let mut drag = tuning.drag;
+    if dampeners.0 && input.thrust == 0.0 {
+        drag += tuning.dampening;
     }
-
-    // Thrust
-    if thrust_input {
-        let forward = player_transform.rotation * Vec3::Y;
-        player_velocity.0 += forward.truncate() * PLAYER_THRUST_FORCE * time.delta_secs();
+    if drag > 0.0 {
+        player_velocity.0 *= (-drag * delta).exp();
+    }
+    if tuning.max_speed > 0.0 {
+        player_velocity.0 = player_velocity.0.clamp_length_max(tuning.max_speed);
     }
 
     // Apply velocity
-    player_transform.translation.x += player_velocity.0.x * time.delta_secs();
-    player_transform.translation.y += player_velocity.0.y * time.delta_secs();
+    player_transform.translation.x += player_velocity.0.x * delta;
+    player_transform.translation.y += player_velocity.0.y * delta;
 }
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, player_movement.run_if(in_state(GameState::Playing)));
+        app.init_resource::<PlayerRespawn>()
+            .add_observer(destroy_ship)
+            .add_systems(
+                FixedUpdate,
+                (
+                    (player_movement, tick_invulnerability).in_set(GameplaySet::Movement),
+                    respawn_player
+                        .in_set(GameplaySet::Spawning)
+                        .after(crate::weapon::fire_weapon),
+                ),
+            )
+            .add_systems(Update, blink_invulnerable);
     }
 }
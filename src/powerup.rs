@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::collider::Collider;
+use crate::collision::{Contacts, PlayerHitPowerUp};
+use crate::edges::EdgeKind;
+use crate::hyperspace::HyperspaceJump;
+use crate::player::{self, Player};
+use crate::rng::GameRng;
+use crate::saucer;
+use crate::timestep::InterpolatedTransform;
+use crate::tuning::{GameTuning, PowerUpTuning};
+use crate::{GameplaySet, Lives};
+
+// Defaults, the values in play come from `GameTuning`
+pub const POWER_UP_DROP_CHANCE: f32 = 0.1;
+pub const POWER_UP_LIFETIME: f32 = 8.0;
+pub const POWER_UP_SPEED: f32 = 30.0;
+pub const POWER_UP_DURATION: f32 = 10.0;
+pub const RAPID_FIRE_INTERVAL: f32 = 0.12;
+pub const TRIPLE_SHOT_SPREAD: f32 = 0.25;
+pub const PIERCING_HITS: u32 = 3;
+
+// Size of a power-up on the screen, and how close the ship has to get to collect it
+const POWER_UP_SIZE: f32 = 18.0;
+
+// A dropped power-up blinks for this many seconds before it's gone
+const EXPIRY_BLINK_TIME: f32 = 2.0;
+
+// Color of a ship with the shield up
+const SHIELD_COLOR: Color = Color::srgb(0.4, 0.9, 1.0);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PowerUpKind {
+    // Holding fire shoots faster
+    RapidFire,
+    // Three bullets fanned out per shot
+    TripleShot,
+    // Nothing can destroy the ship
+    Shield,
+    // Bullets fly on through the asteroids they destroy
+    Piercing,
+    // One more ship, right away
+    ExtraLife,
+}
+
+impl PowerUpKind {
+    // How often each kind drops compared to the others
+    const DROP_WEIGHTS: [(PowerUpKind, u32); 5] = [
+        (PowerUpKind::RapidFire, 3),
+        (PowerUpKind::TripleShot, 3),
+        (PowerUpKind::Shield, 2),
+        (PowerUpKind::Piercing, 3),
+        (PowerUpKind::ExtraLife, 1),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "Rapid fire",
+            PowerUpKind::TripleShot => "Triple shot",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::Piercing => "Piercing",
+            PowerUpKind::ExtraLife => "Extra life",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::RapidFire => Color::srgb(1.0, 0.3, 0.2),
+            PowerUpKind::TripleShot => Color::srgb(1.0, 0.9, 0.2),
+            PowerUpKind::Shield => SHIELD_COLOR,
+            PowerUpKind::Piercing => Color::srgb(0.8, 0.4, 1.0),
+            PowerUpKind::ExtraLife => Color::srgb(0.3, 1.0, 0.3),
+        }
+    }
+
+    fn random(rng: &mut GameRng) -> Self {
+        let total = Self::DROP_WEIGHTS.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.gen_range(0..total);
+        for (kind, weight) in Self::DROP_WEIGHTS {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        unreachable!("the roll is below the total weight")
+    }
+}
+
+// A power-up drifting in the play field, waiting to be collected
+#[derive(Component)]
+pub struct PowerUp {
+    pub velocity: Vec2,
+    lifetime: Timer,
+}
+
+// The power-ups the ship has collected. Each one lasts `power_up.duration` seconds, and
+// collecting one that is already active starts its time over rather than adding to it.
+// Different kinds work together, e.g. triple shot with piercing fires three piercing bullets.
+// They are lost with the ship
+#[derive(Component, Debug, Default)]
+pub struct ActivePowerUps {
+    timers: BTreeMap<PowerUpKind, Timer>,
+}
+
+impl ActivePowerUps {
+    pub fn has(&self, kind: PowerUpKind) -> bool {
+        self.timers.contains_key(&kind)
+    }
+
+    // Active power-ups with their seconds left, in a fixed order
+    pub fn remaining(&self) -> impl Iterator<Item = (PowerUpKind, f32)> + '_ {
+        self.timers
+            .iter()
+            .map(|(kind, timer)| (*kind, timer.remaining_secs()))
+    }
+
+    fn activate(&mut self, kind: PowerUpKind, tuning: &PowerUpTuning) {
+        self.timers
+            .insert(kind, Timer::from_seconds(tuning.duration, TimerMode::Once));
+    }
+}
+
+// Left by piercing bullets: how many more asteroids they can fly through
+#[derive(Component)]
+pub struct Piercing(pub u32);
+
+// Roll for a power-up where an asteroid was shot down. Nothing is drawn from the generator
+// when drops are turned off
+pub fn maybe_drop(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    tuning: &PowerUpTuning,
+    position: Vec3,
+) {
+    if tuning.drop_chance <= 0.0 || !rng.gen_bool(tuning.drop_chance as f64) {
+        return;
+    }
+    let kind = PowerUpKind::random(rng);
+    let angle = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+
+    commands.spawn((
+        Sprite {
+            color: kind.color(),
+            custom_size: Some(Vec2::splat(POWER_UP_SIZE)),
+            ..default()
+        },
+        Transform::from_translation(position)
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        GlobalTransform::default(),
+        InterpolatedTransform::default(),
+        PowerUp {
+            velocity: Vec2::from_angle(angle) * tuning.speed,
+            lifetime: Timer::from_seconds(tuning.lifetime, TimerMode::Once),
+        },
+        kind,
+        Collider::circle(POWER_UP_SIZE / 2.0),
+    ));
+}
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (move_power_ups, tick_active_power_ups).in_set(GameplaySet::Movement),
+                (detect_power_up_pickups, collect_power_ups)
+                    .chain()
+                    .in_set(GameplaySet::Collision)
+                    .after(saucer::player_hits_enemy),
+            ),
+        )
+        .add_systems(Update, (blink_expiring_power_ups, show_shield));
+    }
+}
+
+fn move_power_ups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PowerUp, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut power_up, mut transform) in query.iter_mut() {
+        if power_up.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (power_up.velocity * time.delta_secs()).extend(0.0);
+    }
+}
+
+fn tick_active_power_ups(mut query: Query<&mut ActivePowerUps>, time: Res<Time>) {
+    for mut power_ups in query.iter_mut() {
+        // Left alone when there is nothing to tick, so the HUD sees no change
+        if power_ups.timers.is_empty() {
+            continue;
+        }
+        power_ups
+            .timers
+            .retain(|_, timer| !timer.tick(time.delta()).finished());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn detect_power_up_pickups(
+    player_query: Query<(Entity, &Transform, &Collider), (With<Player>, Without<HyperspaceJump>)>,
+    power_up_query: Query<(&Transform, &Collider), With<PowerUp>>,
+    mut contacts: Contacts,
+    mut hits: EventWriter<PlayerHitPowerUp>,
+) {
+    let Ok((player, player_transform, player_collider)) = player_query.single() else {
+        return;
+    };
+
+    // Picking one up doesn't use up the ship, it can collect several at once
+    let mut nearby = Vec::new();
+    contacts.nearby(
+        player_transform,
+        player_collider,
+        EdgeKind::PowerUp,
+        &mut nearby,
+    );
+    for &power_up in &nearby {
+        let Ok((transform, collider)) = power_up_query.get(power_up) else {
+            continue;
+        };
+        if contacts.touching(
+            player_transform,
+            player_collider,
+            transform,
+            collider,
+            EdgeKind::PowerUp,
+        ) {
+            contacts.consume([power_up]);
+            hits.write(PlayerHitPowerUp { player, power_up });
+        }
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    mut hits: EventReader<PlayerHitPowerUp>,
+    mut player_query: Query<&mut ActivePowerUps, With<Player>>,
+    power_up_query: Query<&PowerUpKind, With<PowerUp>>,
+    mut lives: ResMut<Lives>,
+    tuning: Res<GameTuning>,
+) {
+    for hit in hits.read() {
+        let Ok(mut power_ups) = player_query.get_mut(hit.player) else {
+            continue;
+        };
+        let Ok(kind) = power_up_query.get(hit.power_up) else {
+            continue;
+        };
+        commands.entity(hit.power_up).despawn();
+        match kind {
+            PowerUpKind::ExtraLife => lives.0 += 1,
+            kind => power_ups.activate(*kind, &tuning.power_up),
+        }
+        info!("Collected {}", kind.name());
+    }
+}
+
+// Only changes how they look, so it runs every frame rather than every tick
+fn blink_expiring_power_ups(mut query: Query<(&PowerUp, &mut Visibility)>) {
+    for (power_up, mut visibility) in query.iter_mut() {
+        let remaining = power_up.lifetime.remaining_secs();
+        let phase = (remaining * 8.0) as u32;
+        visibility.set_if_neq(
+            if remaining > EXPIRY_BLINK_TIME || phase.is_multiple_of(2) {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+        );
+    }
+}
+
+fn show_shield(mut query: Query<(&ActivePowerUps, &mut Sprite), With<Player>>) {
+    for (power_ups, mut sprite) in query.iter_mut() {
+        let color = if power_ups.has(PowerUpKind::Shield) {
+            SHIELD_COLOR
+        } else {
+            player::PLAYER_COLOR
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
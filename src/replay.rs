@@ -0,0 +1,544 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::asteroid::AsteroidTheme;
+use crate::input::PlayerInput;
+use crate::rng::GameRng;
+use crate::tuning::GameTuning;
+use crate::{GameState, GameplaySet, Godmode, InertialDampeners, PlayField, StartRun};
+
+// Replay files start with these bytes, followed by the format version
+const REPLAY_MAGIC: &[u8; 4] = b"RSRP";
+pub const REPLAY_VERSION: u16 = 1;
+
+// Finished runs are saved in this folder of the platform data directory
+const REPLAY_DIR: &str = "replays";
+const REPLAY_EXTENSION: &str = "rsrp";
+
+const FIRE_FLAG: u8 = 1 << 0;
+const HYPERSPACE_FLAG: u8 = 1 << 1;
+const FIRE_HELD_FLAG: u8 = 1 << 2;
+
+const GODMODE_FLAG: u8 = 1 << 0;
+const DAMPENERS_FLAG: u8 = 1 << 1;
+const SPRITES_FLAG: u8 = 1 << 2;
+
+// Bytes of input stored per tick
+const TICK_SIZE: usize = 9;
+
+// Everything needed to play a run again: the settings it was started with and the input of
+// every simulation tick.
+//
+// Binary layout (version 1), all numbers little endian:
+// - magic `RSRP`, version u16
+// - seed u64, timestep in nanoseconds u64, play field width f32 and height f32, settings u8
+//   (bit 0 godmode, bit 1 inertial dampeners, bit 2 the `Sprites` asteroid theme)
+// - tuning length u32, then the gameplay tuning as RON text
+// - tick count u32, then per tick: rotation f32, thrust f32, flags u8 (bit 0 fire, bit 1
+//   hyperspace, bit 2 fire held)
+//
+// Files of any other version are rejected, the game can't play them back the same
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub timestep: Duration,
+    pub play_field: PlayField,
+    pub godmode: bool,
+    pub inertial_dampeners: bool,
+    pub asteroid_theme: AsteroidTheme,
+    pub tuning: GameTuning,
+    pub inputs: Vec<PlayerInput>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    NotAReplay,
+    UnsupportedVersion(u16),
+    Truncated,
+    InvalidTuning(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "{error}"),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay format version {version} is not supported, expected {REPLAY_VERSION}"
+            ),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::InvalidTuning(error) => {
+                write!(f, "replay has invalid gameplay tuning: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+impl Replay {
+    // The `replays` folder in the platform data directory, e.g.
+    // `~/.local/share/rusteroids/replays` on Linux
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("rusteroids").join(REPLAY_DIR))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Serializing plain numbers and structs to RON can't fail
+        let tuning = ron::to_string(&self.tuning).expect("tuning serializes to RON");
+
+        let mut bytes = Vec::with_capacity(43 + tuning.len() + self.inputs.len() * TICK_SIZE);
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(self.timestep.as_nanos() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.play_field.width.to_le_bytes());
+        bytes.extend_from_slice(&self.play_field.height.to_le_bytes());
+        let mut settings = 0;
+        if self.godmode {
+            settings |= GODMODE_FLAG;
+        }
+        if self.inertial_dampeners {
+            settings |= DAMPENERS_FLAG;
+        }
+        if self.asteroid_theme == AsteroidTheme::Sprites {
+            settings |= SPRITES_FLAG;
+        }
+        bytes.push(settings);
+        bytes.extend_from_slice(&(tuning.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(tuning.as_bytes());
+        bytes.extend_from_slice(&(self.inputs.len() as u32).to_le_bytes());
+        for input in &self.inputs {
+            let mut flags = 0;
+            if input.fire {
+                flags |= FIRE_FLAG;
+            }
+            if input.hyperspace {
+                flags |= HYPERSPACE_FLAG;
+            }
+            if input.fire_held {
+                flags |= FIRE_HELD_FLAG;
+            }
+            bytes.extend_from_slice(&input.rotation.to_le_bytes());
+            bytes.extend_from_slice(&input.thrust.to_le_bytes());
+            bytes.push(flags);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ReplayError> {
+        if take::<4>(&mut bytes).map_err(|_| ReplayError::NotAReplay)? != *REPLAY_MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = u16::from_le_bytes(take(&mut bytes)?);
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(take(&mut bytes)?);
+        let timestep = Duration::from_nanos(u64::from_le_bytes(take(&mut bytes)?));
+        let play_field = PlayField::new(
+            f32::from_le_bytes(take(&mut bytes)?),
+            f32::from_le_bytes(take(&mut bytes)?),
+        );
+        let [settings] = take(&mut bytes)?;
+        let length = u32::from_le_bytes(take(&mut bytes)?) as usize;
+        if bytes.len() < length {
+            return Err(ReplayError::Truncated);
+        }
+        let (tuning, rest) = bytes.split_at(length);
+        bytes = rest;
+        let tuning: GameTuning = ron::de::from_bytes(tuning)
+            .map_err(|error| ReplayError::InvalidTuning(error.to_string()))?;
+        tuning
+            .validate()
+            .map_err(|error| ReplayError::InvalidTuning(error.to_string()))?;
+        let tick_count = u32::from_le_bytes(take(&mut bytes)?);
+        // Checked before reserving room for the inputs, so a broken tick count can't ask for
+        // gigabytes
+        if bytes.len() < (tick_count as usize).saturating_mul(TICK_SIZE) {
+            return Err(ReplayError::Truncated);
+        }
+
+        let mut inputs = Vec::with_capacity(tick_count as usize);
+        for _ in 0..tick_count {
+            let rotation = f32::from_le_bytes(take(&mut bytes)?);
+            let thrust = f32::from_le_bytes(take(&mut bytes)?);
+            let [flags] = take(&mut bytes)?;
+            inputs.push(PlayerInput {
+                rotation,
+                thrust,
+                fire: flags & FIRE_FLAG != 0,
+                hyperspace: flags & HYPERSPACE_FLAG != 0,
+                fire_held: flags & FIRE_HELD_FLAG != 0,
+            });
+        }
+
+        Ok(Self {
+            seed,
+            timestep,
+            play_field,
+            godmode: settings & GODMODE_FLAG != 0,
+            inertial_dampeners: settings & DAMPENERS_FLAG != 0,
+            asteroid_theme: if settings & SPRITES_FLAG != 0 {
+                AsteroidTheme::Sprites
+            } else {
+                AsteroidTheme::Polygons
+            },
+            tuning,
+            inputs,
+        })
+    }
+}
+
+// Split the next `N` bytes off the front of `bytes`
+fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], ReplayError> {
+    let (head, rest) = bytes.split_first_chunk().ok_or(ReplayError::Truncated)?;
+    *bytes = rest;
+    Ok(*head)
+}
+
+// Input of every tick of the current run, saved to `dir` when the run ends
+#[derive(Resource)]
+struct ReplayRecorder {
+    dir: PathBuf,
+    // The tuning and play field the run started with
+    tuning: GameTuning,
+    play_field: PlayField,
+    inputs: Vec<PlayerInput>,
+}
+
+// The replay being played back, feeding its input to the simulation in place of the devices
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    tick: usize,
+}
+
+// Tag component for the playback controls text
+#[derive(Component)]
+struct ReplayControlsText;
+
+// Plays back `playback` if it is set, otherwise records every run to `record_dir`. Without
+// either it does nothing
+pub struct ReplayPlugin {
+    pub playback: Option<Replay>,
+    pub record_dir: Option<PathBuf>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match (&self.playback, &self.record_dir) {
+            (Some(replay), _) => {
+                app.insert_resource(ReplayPlayback {
+                    replay: replay.clone(),
+                    tick: 0,
+                })
+                .add_observer(restart_playback)
+                .add_systems(Startup, spawn_replay_controls)
+                .add_systems(FixedUpdate, play_back_input.in_set(GameplaySet::Input))
+                .add_systems(Update, replay_controls);
+            }
+            (None, Some(dir)) => {
+                app.insert_resource(ReplayRecorder {
+                    dir: dir.clone(),
+                    tuning: GameTuning::default(),
+                    play_field: PlayField::default(),
+                    inputs: Vec::new(),
+                })
+                .add_observer(restart_recording)
+                .add_systems(FixedUpdate, record_input.in_set(GameplaySet::Input))
+                .add_systems(Update, warn_on_tuning_change)
+                .add_systems(OnEnter(GameState::GameOver), save_recording)
+                .add_systems(Last, save_recording.run_if(on_event::<AppExit>));
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn restart_recording(
+    _trigger: Trigger<StartRun>,
+    mut recorder: ResMut<ReplayRecorder>,
+    tuning: Res<GameTuning>,
+    play_field: Res<PlayField>,
+) {
+    recorder.tuning = tuning.clone();
+    recorder.play_field = *play_field;
+    recorder.inputs.clear();
+}
+
+// The replay only stores the tuning and play field the run started with
+fn warn_on_tuning_change(
+    tuning: Res<GameTuning>,
+    play_field: Res<PlayField>,
+    recorder: Res<ReplayRecorder>,
+) {
+    if recorder.inputs.is_empty() {
+        return;
+    }
+    if tuning.is_changed() && *tuning != recorder.tuning {
+        warn!("Gameplay tuning changed during the run, its replay won't play back the same");
+    }
+    if play_field.is_changed() && *play_field != recorder.play_field {
+        warn!("The play field was resized during the run, its replay won't play back the same");
+    }
+}
+
+fn record_input(input: Res<PlayerInput>, mut recorder: ResMut<ReplayRecorder>) {
+    recorder.inputs.push(*input);
+}
+
+fn save_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    fixed_time: Res<Time<Fixed>>,
+    godmode: Res<Godmode>,
+    dampeners: Res<InertialDampeners>,
+    asteroid_theme: Res<AsteroidTheme>,
+) {
+    if recorder.inputs.is_empty() {
+        return;
+    }
+    let replay = Replay {
+        seed: rng.seed(),
+        timestep: fixed_time.timestep(),
+        play_field: recorder.play_field,
+        godmode: godmode.0,
+        inertial_dampeners: dampeners.0,
+        asteroid_theme: *asteroid_theme,
+        tuning: recorder.tuning.clone(),
+        inputs: std::mem::take(&mut recorder.inputs),
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = recorder
+        .dir
+        .join(format!("{timestamp}-{}", replay.seed))
+        .with_extension(REPLAY_EXTENSION);
+    match replay.save(&path) {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(error) => warn!("Could not save replay to {}: {error}", path.display()),
+    }
+}
+
+fn restart_playback(_trigger: Trigger<StartRun>, mut playback: ResMut<ReplayPlayback>) {
+    playback.tick = 0;
+}
+
+fn play_back_input(mut playback: ResMut<ReplayPlayback>, mut input: ResMut<PlayerInput>) {
+    let tick = playback.tick;
+    *input = match playback.replay.inputs.get(tick) {
+        Some(recorded) => *recorded,
+        None => {
+            if tick == playback.replay.inputs.len() {
+                info!("Replay finished after {tick} ticks");
+            }
+            PlayerInput::default()
+        }
+    };
+    playback.tick += 1;
+}
+
+fn spawn_replay_controls(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        children![(
+            Text::new(""),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            ReplayControlsText,
+        )],
+    ));
+}
+
+// P pauses, 1/2/4 pick the playback speed and R starts the replay over
+fn replay_controls(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut text_query: Query<&mut Text, With<ReplayControlsText>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyP) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+    for (key, speed) in [
+        (KeyCode::Digit1, 1.0),
+        (KeyCode::Digit2, 2.0),
+        (KeyCode::Digit4, 4.0),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            time.set_relative_speed(speed);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        commands.trigger(StartRun);
+        game_state.set(GameState::Playing);
+    }
+
+    if let Ok(mut text) = text_query.single_mut() {
+        let status = if time.is_paused() {
+            "paused".to_string()
+        } else {
+            format!("{}x", time.relative_speed())
+        };
+        text.0 = format!("Replay {status}   P: pause   1/2/4: speed   R: restart");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RusteroidsPlugin, Score};
+
+    // A run of `ticks` ticks that turns, thrusts and shoots in a fixed pattern
+    fn scripted_replay(seed: u64, ticks: u32) -> Replay {
+        let inputs = (0..ticks)
+            .map(|tick| PlayerInput {
+                rotation: if tick % 240 < 120 { 0.5 } else { -0.3 },
+                thrust: if tick % 300 < 40 { 1.0 } else { 0.0 },
+                fire: tick % 12 == 0,
+                fire_held: tick % 12 < 4,
+                hyperspace: tick % 1000 == 999,
+            })
+            .collect();
+        Replay {
+            seed,
+            timestep: Duration::from_secs_f64(1.0 / 60.0),
+            play_field: PlayField::new(1024.0, 768.0),
+            godmode: false,
+            inertial_dampeners: true,
+            asteroid_theme: AsteroidTheme::Polygons,
+            tuning: GameTuning::default(),
+            inputs,
+        }
+    }
+
+    // Play `replay` headless until the game is over, or for at most `max_updates` ticks, and
+    // return the score
+    fn play(replay: Replay, max_updates: u32) -> u32 {
+        let mut app = App::new();
+        app.add_plugins(
+            RusteroidsPlugin::new()
+                .headless(true)
+                .with_persistent_settings(false)
+                .with_replay(replay),
+        );
+        for _ in 0..max_updates {
+            app.update();
+            if app.should_exit().is_some() {
+                break;
+            }
+        }
+        app.world().resource::<Score>().0
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut replay = scripted_replay(42, 500);
+        replay.godmode = true;
+        replay.inertial_dampeners = false;
+        replay.asteroid_theme = AsteroidTheme::Sprites;
+        replay.tuning.player.lives = 5;
+        let bytes = replay.to_bytes();
+        assert_eq!(Replay::from_bytes(&bytes).unwrap(), replay);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = scripted_replay(42, 10).to_bytes();
+        for length in [0, 3, 5, 20, 40, bytes.len() / 2, bytes.len() - 1] {
+            let result = Replay::from_bytes(&bytes[..length]);
+            assert!(
+                matches!(
+                    result,
+                    Err(ReplayError::Truncated | ReplayError::NotAReplay)
+                ),
+                "{length} bytes gave {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_huge_tick_count() {
+        let mut bytes = scripted_replay(42, 10).to_bytes();
+        let count_at = bytes.len() - 10 * TICK_SIZE - 4;
+        bytes[count_at..count_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = scripted_replay(42, 10).to_bytes();
+        bytes[0] = b'X';
+        assert!(matches!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::NotAReplay)
+        ));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = scripted_replay(42, 10).to_bytes();
+        for version in [0, REPLAY_VERSION + 1] {
+            bytes[4..6].copy_from_slice(&version.to_le_bytes());
+            assert!(matches!(
+                Replay::from_bytes(&bytes),
+                Err(ReplayError::UnsupportedVersion(v)) if v == version
+            ));
+        }
+    }
+
+    #[test]
+    fn same_seed_and_input_give_the_same_score() {
+        let replay = scripted_replay(7, 3000);
+        let first = play(replay.clone(), 6000);
+        let second = play(replay, 6000);
+        assert!(first > 0, "the scripted run should hit something");
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+
+// Seed requested at launch. When it is not set, every run picks a fresh random seed
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct SeedSetting(pub Option<u64>);
+
+impl SeedSetting {
+    // Seed for the next run
+    pub fn next_seed(&self) -> u64 {
+        self.0.unwrap_or_else(rand::random)
+    }
+}
+
+// The single source of randomness for everything that affects the simulation. As long as all
+// gameplay systems draw from it, two runs with the same seed and inputs play out the same way
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // The seed the current run was started with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Restart the sequence from a new seed, done at the start of every run
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+// Implementing `RngCore` gives `GameRng` all the `rand::Rng` helpers like `gen_range`
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,438 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::collider::Collider;
+use crate::collision::{BulletHitEnemy, Contacts, PlayerHitEnemy};
+use crate::edges::{self, EdgeKind};
+use crate::events::{SaucerDestroyed, Shooter, ShotFired};
+use crate::hyperspace::{self, HyperspaceJump};
+use crate::mechanics::{self, Bullet, BulletLifetime, BulletVelocity, Damage, Health};
+use crate::player::{self, Player, ShipDestroyed, ShipLoss};
+use crate::powerup::{ActivePowerUps, PowerUpKind};
+use crate::rng::GameRng;
+use crate::timestep::InterpolatedTransform;
+use crate::tuning::GameTuning;
+use crate::{GameplaySet, Godmode, PlayField, StartRun};
+
+// Defaults, the values in play come from `GameTuning`
+pub const SAUCER_SPAWN_INTERVAL: f32 = 15.0;
+pub const SAUCER_SMALL_CHANCE: f32 = 0.3;
+pub const SAUCER_LARGE_SIZE: f32 = 60.0;
+pub const SAUCER_SMALL_SIZE: f32 = 36.0;
+pub const SAUCER_LARGE_SPEED: f32 = 90.0;
+pub const SAUCER_SMALL_SPEED: f32 = 140.0;
+pub const SAUCER_TURN_INTERVAL: f32 = 1.5;
+pub const SAUCER_FIRE_INTERVAL: f32 = 1.2;
+pub const SAUCER_BULLET_SPEED: f32 = 300.0;
+pub const SAUCER_LARGE_AIM_ERROR: f32 = std::f32::consts::PI;
+pub const SAUCER_SMALL_AIM_ERROR: f32 = 0.15;
+pub const SAUCER_STAY_TIME: f32 = 12.0;
+
+// Height of a saucer for its width
+const SAUCER_HEIGHT: f32 = 0.45;
+
+const SAUCER_BULLET_SIZE: f32 = 8.0;
+
+// The large saucer is slow and fires anywhere, the small one is fast and aims at the ship
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaucerSize {
+    Large,
+    Small,
+}
+
+impl SaucerSize {
+    pub fn color(&self) -> Color {
+        match self {
+            SaucerSize::Large => Color::srgb(0.4, 0.9, 0.5),
+            SaucerSize::Small => Color::srgb(0.9, 0.4, 0.9),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Saucer {
+    // Until the next change of course
+    turn: Timer,
+    // Until the next shot
+    fire: Timer,
+    // Once it runs out, the saucer leaves at the next edge it reaches instead of wrapping
+    stay: Timer,
+}
+
+impl Saucer {
+    pub fn is_leaving(&self) -> bool {
+        self.stay.finished()
+    }
+}
+
+#[derive(Component)]
+pub struct SaucerVelocity(pub Vec2);
+
+// Marks bullets fired by a saucer. They destroy asteroids and the ship like any bullet, but
+// score nothing and don't count as missed shots
+#[derive(Component)]
+pub struct SaucerBullet;
+
+// Counts down to the next saucer. Only one saucer is in play at a time
+#[derive(Resource)]
+pub struct SaucerSpawnTimer(pub Timer);
+
+impl Default for SaucerSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            SAUCER_SPAWN_INTERVAL,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+pub fn spawn_saucer(
+    commands: &mut Commands,
+    size: SaucerSize,
+    position: Vec3,
+    velocity: Vec2,
+    tuning: &GameTuning,
+) {
+    let width = tuning.saucer.size(size);
+    let height = width * SAUCER_HEIGHT;
+    let collider = if tuning.collision.outlines {
+        Collider::rectangle(Vec2::new(width, height))
+    } else {
+        Collider::circle(width / 2.0)
+    };
+    let tuning = &tuning.saucer;
+
+    commands.spawn((
+        Sprite {
+            color: size.color(),
+            custom_size: Some(Vec2::new(width, height)),
+            ..default()
+        },
+        Transform::from_translation(position),
+        GlobalTransform::default(),
+        InterpolatedTransform::default(),
+        Saucer {
+            turn: Timer::from_seconds(tuning.turn_interval, TimerMode::Repeating),
+            fire: Timer::from_seconds(tuning.fire_interval, TimerMode::Repeating),
+            stay: Timer::from_seconds(tuning.stay_time, TimerMode::Once),
+        },
+        size,
+        SaucerVelocity(velocity),
+        Health(tuning.hit_points),
+        collider,
+    ));
+}
+
+pub struct SaucerPlugin;
+
+impl Plugin for SaucerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaucerSpawnTimer>()
+            .add_observer(reset_saucer_timer)
+            .add_systems(
+                FixedUpdate,
+                (
+                    // They draw from the game's generator, so their order has to be fixed
+                    (steer_saucers, move_saucers)
+                        .chain()
+                        .in_set(GameplaySet::Movement)
+                        .after(hyperspace::start_jump),
+                    leave_play_field
+                        .in_set(GameplaySet::Bounds)
+                        .before(edges::saucers_at_edges),
+                    (
+                        detect_bullet_enemy_hits,
+                        bullet_hits_enemy,
+                        detect_player_enemy_hits,
+                        player_hits_enemy,
+                    )
+                        .chain()
+                        .in_set(GameplaySet::Collision)
+                        .after(mechanics::player_hits_asteroid),
+                    (saucer_fire, spawn_saucers_over_time)
+                        .chain()
+                        .in_set(GameplaySet::Spawning)
+                        .after(crate::wave::advance_wave)
+                        .after(player::respawn_player),
+                ),
+            );
+    }
+}
+
+fn reset_saucer_timer(_trigger: Trigger<StartRun>, mut timer: ResMut<SaucerSpawnTimer>) {
+    timer.0.reset();
+}
+
+fn spawn_saucers_over_time(
+    mut commands: Commands,
+    mut timer: ResMut<SaucerSpawnTimer>,
+    saucer_query: Query<(), With<Saucer>>,
+    play_field: Res<PlayField>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    if !tuning.saucer.enabled {
+        return;
+    }
+
+    // Pick up changes to the spawn interval from the tuning file
+    let interval = Duration::from_secs_f32(tuning.saucer.spawn_interval);
+    if timer.0.duration() != interval {
+        timer.0.set_duration(interval);
+    }
+
+    if !timer.0.tick(time.delta()).just_finished() || !saucer_query.is_empty() {
+        return;
+    }
+
+    let size = if rng.gen_bool(tuning.saucer.small_chance as f64) {
+        SaucerSize::Small
+    } else {
+        SaucerSize::Large
+    };
+    // In from the left or right edge, heading across the screen
+    let direction = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let half_height = play_field.half_height();
+    let position = Vec3::new(
+        -direction * play_field.half_width(),
+        rng.gen_range(-half_height..half_height),
+        0.0,
+    );
+    let velocity = Vec2::new(direction * tuning.saucer.speed(size), 0.0);
+    spawn_saucer(&mut commands, size, position, velocity, &tuning);
+}
+
+// Now and then a saucer swings up or down by 45 degrees, or levels out
+fn steer_saucers(
+    mut query: Query<(&mut Saucer, &mut SaucerVelocity, &SaucerSize)>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (mut saucer, mut velocity, size) in query.iter_mut() {
+        if saucer.turn.tick(time.delta()).just_finished() {
+            let heading = Vec2::new(velocity.0.x.signum(), rng.gen_range(-1..=1) as f32);
+            velocity.0 = heading.normalize() * tuning.saucer.speed(*size);
+        }
+    }
+}
+
+fn move_saucers(mut query: Query<(&mut Transform, &SaucerVelocity)>, time: Res<Time>) {
+    for (mut transform, velocity) in query.iter_mut() {
+        transform.translation.x += velocity.0.x * time.delta_secs();
+        transform.translation.y += velocity.0.y * time.delta_secs();
+    }
+}
+
+// Saucers wrap around the screen like everything else until their time is up, then fly off
+fn leave_play_field(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Saucer, &Transform)>,
+    play_field: Res<PlayField>,
+    time: Res<Time>,
+) {
+    for (entity, mut saucer, transform) in query.iter_mut() {
+        let translation = transform.translation;
+        let outside = translation.x.abs() > play_field.half_width()
+            || translation.y.abs() > play_field.half_height();
+        if saucer.stay.tick(time.delta()).finished() && outside {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn saucer_fire(
+    mut commands: Commands,
+    mut saucer_query: Query<(&mut Saucer, &Transform, &SaucerSize)>,
+    player_query: Query<&Transform, (With<Player>, Without<HyperspaceJump>)>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (mut saucer, transform, size) in saucer_query.iter_mut() {
+        if !saucer.fire.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        // Aim at the ship if there is one to aim at, and miss by up to the aim error
+        let aim = match player_query.single() {
+            Ok(player) => (player.translation - transform.translation)
+                .truncate()
+                .to_angle(),
+            Err(_) => rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI),
+        };
+        let error = tuning.saucer.aim_error(*size);
+        let angle = aim + rng.gen_range(-error..=error);
+        let direction = Vec2::from_angle(angle);
+        let position =
+            transform.translation + (direction * tuning.saucer.size(*size) / 2.0).extend(0.0);
+
+        commands.spawn((
+            Sprite {
+                color: Color::srgb(0.5, 1.0, 0.5),
+                custom_size: Some(Vec2::splat(SAUCER_BULLET_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(position),
+            GlobalTransform::default(),
+            InterpolatedTransform::default(),
+            Bullet,
+            SaucerBullet,
+            BulletVelocity(direction * tuning.saucer.bullet_speed),
+            BulletLifetime(Timer::from_seconds(tuning.bullet.lifetime, TimerMode::Once)),
+            Damage(1),
+            Collider::circle(SAUCER_BULLET_SIZE / 2.0),
+        ));
+        commands.trigger(ShotFired {
+            position: transform.translation,
+            direction,
+            by: Shooter::Saucer,
+        });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn detect_bullet_enemy_hits(
+    bullet_query: Query<(Entity, &Transform, &Collider), (With<Bullet>, Without<SaucerBullet>)>,
+    saucer_query: Query<(&Transform, &Collider), With<Saucer>>,
+    mut contacts: Contacts,
+    mut hits: EventWriter<BulletHitEnemy>,
+) {
+    let mut nearby = Vec::new();
+
+    for (bullet, bullet_transform, bullet_collider) in bullet_query.iter() {
+        if contacts.is_consumed(bullet) {
+            continue;
+        }
+        contacts.nearby(
+            bullet_transform,
+            bullet_collider,
+            EdgeKind::Saucer,
+            &mut nearby,
+        );
+        for &enemy in &nearby {
+            let Ok((saucer_transform, saucer_collider)) = saucer_query.get(enemy) else {
+                continue;
+            };
+            if contacts.touching(
+                bullet_transform,
+                bullet_collider,
+                saucer_transform,
+                saucer_collider,
+                EdgeKind::Saucer,
+            ) {
+                contacts.consume([bullet, enemy]);
+                hits.write(BulletHitEnemy { bullet, enemy });
+                break;
+            }
+        }
+    }
+}
+
+fn bullet_hits_enemy(
+    mut commands: Commands,
+    mut hits: EventReader<BulletHitEnemy>,
+    bullet_query: Query<&Damage, With<Bullet>>,
+    mut saucer_query: Query<(&Transform, &SaucerSize, &mut Health), With<Saucer>>,
+) {
+    for hit in hits.read() {
+        let Ok(damage) = bullet_query.get(hit.bullet) else {
+            continue;
+        };
+        let Ok((transform, saucer_size, mut health)) = saucer_query.get_mut(hit.enemy) else {
+            continue;
+        };
+        commands.entity(hit.bullet).despawn();
+        health.0 = health.0.saturating_sub(damage.0);
+        if health.0 > 0 {
+            continue;
+        }
+        commands.entity(hit.enemy).despawn();
+        commands.trigger(SaucerDestroyed {
+            size: *saucer_size,
+            position: transform.translation,
+        });
+    }
+}
+
+// Saucers come before their bullets
+#[allow(clippy::type_complexity)]
+fn detect_player_enemy_hits(
+    player_query: Query<
+        (Entity, &Transform, &Collider),
+        (
+            With<Player>,
+            Without<player::Invulnerable>,
+            Without<HyperspaceJump>,
+        ),
+    >,
+    saucer_query: Query<(&Transform, &Collider), With<Saucer>>,
+    bullet_query: Query<(&Transform, &Collider), With<SaucerBullet>>,
+    mut contacts: Contacts,
+    mut hits: EventWriter<PlayerHitEnemy>,
+) {
+    let Ok((player, player_transform, player_collider)) = player_query.single() else {
+        return;
+    };
+    if contacts.is_consumed(player) {
+        return;
+    }
+
+    let mut nearby = Vec::new();
+    for kind in [EdgeKind::Saucer, EdgeKind::Bullet] {
+        contacts.nearby(player_transform, player_collider, kind, &mut nearby);
+        for &enemy in &nearby {
+            let enemy_query = match kind {
+                EdgeKind::Saucer => saucer_query.get(enemy),
+                _ => bullet_query.get(enemy),
+            };
+            let Ok((enemy_transform, enemy_collider)) = enemy_query else {
+                continue;
+            };
+            if contacts.touching(
+                player_transform,
+                player_collider,
+                enemy_transform,
+                enemy_collider,
+                kind,
+            ) {
+                contacts.consume([player, enemy]);
+                hits.write(PlayerHitEnemy { player, enemy });
+                return;
+            }
+        }
+    }
+}
+
+// Saucer bullets and the saucers themselves are as deadly to the ship as asteroids. Ramming a
+// saucer takes it down too, and still scores. A shielded ship survives both, and the shield
+// stops the bullet
+pub fn player_hits_enemy(
+    mut commands: Commands,
+    mut hits: EventReader<PlayerHitEnemy>,
+    player_query: Query<&ActivePowerUps, With<Player>>,
+    saucer_query: Query<(&Transform, &SaucerSize), With<Saucer>>,
+    godmode: Res<Godmode>,
+) {
+    for hit in hits.read() {
+        let Ok(power_ups) = player_query.get(hit.player) else {
+            continue;
+        };
+        if godmode.0 {
+            continue;
+        }
+        commands.entity(hit.enemy).despawn();
+        if let Ok((transform, saucer_size)) = saucer_query.get(hit.enemy) {
+            commands.trigger(SaucerDestroyed {
+                size: *saucer_size,
+                position: transform.translation,
+            });
+        }
+        if !power_ups.has(PowerUpKind::Shield) {
+            commands.trigger_targets(ShipDestroyed(ShipLoss::Saucer), hit.player);
+        }
+    }
+}
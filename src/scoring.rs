@@ -0,0 +1,149 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::events::{AsteroidDestroyed, PlayerDied, SaucerDestroyed, Shooter, WaveCleared};
+use crate::tuning::GameTuning;
+use crate::{GameplaySet, Lives, Score, StartRun};
+
+// Defaults, the values in play come from `GameTuning`
+pub const EXTRA_LIFE_EVERY: u32 = 10_000;
+pub const COMBO_WINDOW: f32 = 2.0;
+pub const COMBO_KILLS_PER_STEP: u32 = 3;
+pub const COMBO_MAX_MULTIPLIER: u32 = 5;
+
+// What the points are for. Only kills count towards the combo and get multiplied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointsFor {
+    Kill,
+    Bonus,
+}
+
+// Kills in quick succession, each within `score.combo_window` seconds of the last. The streak
+// breaks when that time runs out, a shot misses or the ship is lost
+#[derive(Resource, Debug, Default)]
+pub struct Combo {
+    pub streak: u32,
+    // Counts down from the last kill
+    timer: Timer,
+}
+
+impl Combo {
+    pub fn multiplier(&self, tuning: &GameTuning) -> u32 {
+        let steps = self.streak.saturating_sub(1) / tuning.score.combo_kills_per_step;
+        (1 + steps).min(tuning.score.combo_max_multiplier)
+    }
+
+    pub fn break_streak(&mut self) {
+        self.streak = 0;
+    }
+}
+
+// The one way to award points. It applies the combo multiplier and hands out extra lives
+// when the score passes a threshold
+#[derive(SystemParam)]
+pub struct Scoring<'w> {
+    score: ResMut<'w, Score>,
+    lives: ResMut<'w, Lives>,
+    combo: ResMut<'w, Combo>,
+    tuning: Res<'w, GameTuning>,
+}
+
+impl Scoring<'_> {
+    // Add `points` to the score and return how many were actually awarded
+    pub fn award(&mut self, points: u32, points_for: PointsFor) -> u32 {
+        let points = match points_for {
+            PointsFor::Kill => {
+                self.combo.streak += 1;
+                let window = self.tuning.score.combo_window;
+                self.combo.timer = Timer::from_seconds(window, TimerMode::Once);
+                points.saturating_mul(self.combo.multiplier(&self.tuning))
+            }
+            PointsFor::Bonus => points,
+        };
+
+        let previous = self.score.0;
+        self.score.0 = previous.saturating_add(points);
+        let extra_lives = extra_lives_between(&self.tuning, previous, self.score.0);
+        if extra_lives > 0 {
+            self.lives.0 += extra_lives;
+            info!("Extra life at {} points", self.score.0);
+        }
+        points
+    }
+}
+
+// Number of extra life thresholds passed when the score goes from `from` to `to`
+fn extra_lives_between(tuning: &GameTuning, from: u32, to: u32) -> u32 {
+    let listed = tuning
+        .score
+        .extra_life_at
+        .iter()
+        .filter(|&&threshold| from < threshold && threshold <= to)
+        .count() as u32;
+    let every = match tuning.score.extra_life_every {
+        0 => 0,
+        every => to / every - from / every,
+    };
+    listed + every
+}
+
+// The lowest score above `score` that earns an extra life, if any
+pub fn next_extra_life(tuning: &GameTuning, score: u32) -> Option<u32> {
+    let listed = tuning
+        .score
+        .extra_life_at
+        .iter()
+        .copied()
+        .filter(|&threshold| threshold > score)
+        .min();
+    let every = match tuning.score.extra_life_every {
+        0 => None,
+        every => (score / every).checked_add(1)?.checked_mul(every),
+    };
+    listed.into_iter().chain(every).min()
+}
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_observer(reset_combo)
+            .add_observer(score_asteroid)
+            .add_observer(score_saucer)
+            .add_observer(score_wave)
+            .add_observer(break_combo_on_death)
+            .add_systems(FixedUpdate, tick_combo.in_set(GameplaySet::Movement));
+    }
+}
+
+fn reset_combo(_trigger: Trigger<StartRun>, mut combo: ResMut<Combo>) {
+    *combo = Combo::default();
+}
+
+fn tick_combo(mut combo: ResMut<Combo>, time: Res<Time>) {
+    if combo.streak > 0 && combo.timer.tick(time.delta()).finished() {
+        combo.break_streak();
+    }
+}
+
+// Saucers shoot asteroids too, but only the player scores
+fn score_asteroid(trigger: Trigger<AsteroidDestroyed>, mut scoring: Scoring) {
+    let event = trigger.event();
+    if event.by == Shooter::Player {
+        let points = scoring.tuning.score.asteroid_points(event.size);
+        scoring.award(points, PointsFor::Kill);
+    }
+}
+
+fn score_saucer(trigger: Trigger<SaucerDestroyed>, mut scoring: Scoring) {
+    let points = scoring.tuning.score.saucer_points(trigger.event().size);
+    scoring.award(points, PointsFor::Kill);
+}
+
+fn score_wave(trigger: Trigger<WaveCleared>, mut scoring: Scoring) {
+    scoring.award(trigger.event().bonus, PointsFor::Bonus);
+}
+
+fn break_combo_on_death(_trigger: Trigger<PlayerDied>, mut combo: ResMut<Combo>) {
+    combo.break_streak();
+}
@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+// Bump this when a setting changes meaning. Files with another version are ignored
+pub const SETTINGS_VERSION: u32 = 1;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+// Where the game keeps files that outlive a run, e.g. `~/.config/rusteroids` on Linux
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("rusteroids"))
+}
+
+// A value the player can change in the menu and that is kept across runs. The setting is a
+// resource in the app, and a component on the menu buttons that select it, see
+// `menu::setting_button`. Register it with `App::register_setting`
+pub trait Setting: Resource + Component + Clone + Default + Serialize + DeserializeOwned {
+    // Name of the setting in the settings file. Don't change it, or saved values are lost
+    const KEY: &'static str;
+}
+
+// The settings file as stored on disk. Every setting is kept as a raw RON value, so
+// settings the running build doesn't know about are written back unchanged
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    settings: BTreeMap<String, ron::Value>,
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    Ron(ron::error::SpannedError),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(error) => write!(f, "{error}"),
+            SettingsError::Ron(error) => write!(f, "could not parse settings: {error}"),
+            SettingsError::UnsupportedVersion(version) => write!(
+                f,
+                "settings version {version} is not supported, expected {SETTINGS_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+// All saved settings, and where they are saved. Without a path nothing is read or written
+#[derive(Resource, Debug, Default)]
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    values: BTreeMap<String, ron::Value>,
+}
+
+impl SettingsStore {
+    // `settings.ron` in the platform config directory
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    // Read the settings saved at `path`. A missing file is an empty store, any other
+    // problem is logged and the defaults are used
+    pub fn load(path: PathBuf) -> Self {
+        let values = match Self::read(&path) {
+            Ok(values) => values,
+            Err(SettingsError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                BTreeMap::new()
+            }
+            Err(error) => {
+                warn!(
+                    "Could not load settings from {}, using defaults: {error}",
+                    path.display()
+                );
+                BTreeMap::new()
+            }
+        };
+        Self {
+            path: Some(path),
+            values,
+        }
+    }
+
+    fn read(path: &PathBuf) -> Result<BTreeMap<String, ron::Value>, SettingsError> {
+        let text = fs::read_to_string(path).map_err(SettingsError::Io)?;
+        let file: SettingsFile = ron::from_str(&text).map_err(SettingsError::Ron)?;
+        if file.version != SETTINGS_VERSION {
+            return Err(SettingsError::UnsupportedVersion(file.version));
+        }
+        Ok(file.settings)
+    }
+
+    // The saved value of `T`, if there is one and it is valid
+    pub fn get<T: Setting>(&self) -> Option<T> {
+        let value = self.values.get(T::KEY)?;
+        match value.clone().into_rust() {
+            Ok(setting) => Some(setting),
+            Err(error) => {
+                warn!("Ignoring saved setting {}: {error}", T::KEY);
+                None
+            }
+        }
+    }
+
+    // Change the value of `T` and write all settings to disk
+    pub fn set<T: Setting>(&mut self, setting: &T) {
+        // Plain values always convert to and from RON
+        let text = ron::to_string(setting).expect("setting serializes to RON");
+        let value = ron::from_str(&text).expect("setting converts to a RON value");
+        self.values.insert(T::KEY.to_string(), value);
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        let file = SettingsFile {
+            version: SETTINGS_VERSION,
+            settings: self.values.clone(),
+        };
+        let text = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+            .expect("settings serialize to RON");
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(path, text)),
+            None => fs::write(path, text),
+        };
+        if let Err(error) = result {
+            warn!("Could not save settings to {}: {error}", path.display());
+        }
+    }
+}
+
+pub trait AppSettingsExt {
+    // Insert the setting with its saved value, or its default, and save it whenever it
+    // changes. An `override_value`, e.g. from the command line, is used for this run only
+    fn register_setting<T: Setting>(&mut self, override_value: Option<T>) -> &mut Self;
+}
+
+impl AppSettingsExt for App {
+    fn register_setting<T: Setting>(&mut self, override_value: Option<T>) -> &mut Self {
+        let store = self.world_mut().get_resource_or_init::<SettingsStore>();
+        let setting = override_value
+            .or_else(|| store.get::<T>())
+            .unwrap_or_default();
+        self.insert_resource(setting).add_systems(
+            Update,
+            save_setting::<T>.run_if(resource_changed::<T>.and(not(resource_added::<T>))),
+        )
+    }
+}
+
+fn save_setting<T: Setting>(setting: Res<T>, mut store: ResMut<SettingsStore>) {
+    store.set(&*setting);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    struct Volume(u32);
+
+    impl Setting for Volume {
+        const KEY: &'static str = "volume";
+    }
+
+    // A settings file of its own for each test, holding `text` if there is some
+    struct TempSettings(PathBuf);
+
+    impl TempSettings {
+        fn new(name: &str, text: Option<&str>) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "rusteroids-settings-{name}-{}.ron",
+                std::process::id()
+            ));
+            match text {
+                Some(text) => fs::write(&path, text).unwrap(),
+                None => {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+            Self(path)
+        }
+    }
+
+    impl Drop for TempSettings {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn file_with(version: u32, settings: &str) -> String {
+        format!("(version: {version}, settings: {{{settings}}})")
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let file = TempSettings::new("missing", None);
+        let store = SettingsStore::load(file.0.clone());
+        assert_eq!(store.get::<Volume>(), None);
+    }
+
+    #[test]
+    fn saved_settings_load_back() {
+        let file = TempSettings::new("round-trip", None);
+        SettingsStore::load(file.0.clone()).set(&Volume(3));
+        assert_eq!(SettingsStore::load(file.0.clone()).get(), Some(Volume(3)));
+    }
+
+    #[test]
+    fn unknown_settings_are_kept() {
+        let text = file_with(SETTINGS_VERSION, r#""from_a_newer_build": true"#);
+        let file = TempSettings::new("unknown", Some(&text));
+        SettingsStore::load(file.0.clone()).set(&Volume(3));
+        let saved = fs::read_to_string(&file.0).unwrap();
+        assert!(saved.contains("from_a_newer_build"), "{saved}");
+    }
+
+    #[test]
+    fn corrupt_file_falls_back_to_defaults() {
+        let file = TempSettings::new("corrupt", Some("(version: 1, settings: {\"volume\""));
+        let store = SettingsStore::load(file.0.clone());
+        assert_eq!(store.get::<Volume>(), None);
+    }
+
+    #[test]
+    fn other_version_falls_back_to_defaults() {
+        let text = file_with(SETTINGS_VERSION + 1, r#""volume": (7)"#);
+        let file = TempSettings::new("version", Some(&text));
+        let store = SettingsStore::load(file.0.clone());
+        assert_eq!(store.get::<Volume>(), None);
+    }
+
+    #[test]
+    fn setting_of_the_wrong_type_is_ignored() {
+        let text = file_with(SETTINGS_VERSION, r#""volume": "loud""#);
+        let file = TempSettings::new("wrong-type", Some(&text));
+        let store = SettingsStore::load(file.0.clone());
+        assert_eq!(store.get::<Volume>(), None);
+    }
+
+    #[test]
+    fn registered_setting_uses_the_default_after_a_corrupt_file() {
+        let file = TempSettings::new("register", Some("not ron at all"));
+        let mut app = App::new();
+        app.insert_resource(SettingsStore::load(file.0.clone()))
+            .register_setting::<Volume>(None);
+        assert_eq!(*app.world().resource::<Volume>(), Volume::default());
+    }
+
+    #[test]
+    fn override_is_not_saved() {
+        let text = file_with(SETTINGS_VERSION, r#""volume": (2)"#);
+        let file = TempSettings::new("override", Some(&text));
+        let mut app = App::new();
+        app.insert_resource(SettingsStore::load(file.0.clone()))
+            .register_setting(Some(Volume(9)));
+        app.update();
+        assert_eq!(*app.world().resource::<Volume>(), Volume(9));
+        assert_eq!(SettingsStore::load(file.0.clone()).get(), Some(Volume(2)));
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::GameplaySet;
+use crate::collider::Collider;
+use crate::mechanics;
+
+// Width and height of a grid cell. About the size of a large asteroid, so most things only
+// cover a few cells
+pub const SPATIAL_CELL_SIZE: f32 = 64.0;
+
+// A uniform grid of everything with a collider, rebuilt at the start of every tick's collision
+// checks. The collision systems only test the pairs it puts together, instead of everything
+// against everything. Things spawned during the checks, like asteroid fragments, are added as
+// they spawn. Things despawned meanwhile are still listed, so the systems skip the entities
+// they can't find
+#[derive(Resource, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    // Each entry is the order the entity was inserted in, and the entity
+    cells: HashMap<IVec2, Vec<(u32, Entity)>>,
+    inserted: u32,
+    // Of everything inserted, so queries know how far past an edge something can reach
+    max_radius: f32,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(SPATIAL_CELL_SIZE)
+    }
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            inserted: 0,
+            max_radius: 0.0,
+        }
+    }
+
+    // Empty every cell, keeping the memory for the next tick
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.inserted = 0;
+        self.max_radius = 0.0;
+    }
+
+    pub fn max_radius(&self) -> f32 {
+        self.max_radius
+    }
+
+    // Add `entity` to every cell its circle covers
+    pub fn insert(&mut self, entity: Entity, center: Vec2, radius: f32) {
+        let order = self.inserted;
+        self.inserted += 1;
+        self.max_radius = self.max_radius.max(radius);
+        let (min, max) = self.cell_range(center, radius);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells
+                    .entry(IVec2::new(x, y))
+                    .or_default()
+                    .push((order, entity));
+            }
+        }
+    }
+
+    fn insert_collider(&mut self, entity: Entity, transform: &Transform, collider: &Collider) {
+        self.insert(
+            entity,
+            transform.translation.truncate(),
+            collider.radius * transform.scale.x,
+        );
+    }
+
+    // Everything that may touch the circle, each once and in the order it was inserted, so the
+    // collision systems handle hits in the same order every run
+    pub fn query(&self, center: Vec2, radius: f32, found: &mut Vec<Entity>) {
+        self.query_around(&[center], radius, found);
+    }
+
+    // Everything that may touch a circle at any of `centers`, like `query`. Used to look at
+    // the copies of a circle on the other side of the edges of the play field
+    pub fn query_around(&self, centers: &[Vec2], radius: f32, found: &mut Vec<Entity>) {
+        let mut entries = Vec::new();
+        for &center in centers {
+            let (min, max) = self.cell_range(center, radius);
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+                        entries.extend_from_slice(cell);
+                    }
+                }
+            }
+        }
+        entries.sort_unstable_by_key(|(order, _)| *order);
+        entries.dedup_by_key(|(order, _)| *order);
+
+        found.clear();
+        found.extend(entries.into_iter().map(|(_, entity)| entity));
+    }
+
+    // Everything that may touch `collider` at `transform`, like `query`
+    pub fn nearby(&self, transform: &Transform, collider: &Collider, found: &mut Vec<Entity>) {
+        self.query(
+            transform.translation.truncate(),
+            collider.radius * transform.scale.x,
+            found,
+        );
+    }
+
+    fn cell_range(&self, center: Vec2, radius: f32) -> (IVec2, IVec2) {
+        let min = ((center - radius) / self.cell_size).floor().as_ivec2();
+        let max = ((center + radius) / self.cell_size).floor().as_ivec2();
+        (min, max)
+    }
+}
+
+pub struct SpatialHashPlugin;
+
+impl Plugin for SpatialHashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialHash>()
+            .add_observer(insert_spawned)
+            .add_systems(
+                FixedUpdate,
+                rebuild_spatial_hash
+                    .in_set(GameplaySet::Collision)
+                    .before(mechanics::detect_bullet_asteroid_hits),
+            );
+    }
+}
+
+fn rebuild_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    query: Query<(Entity, &Transform, &Collider)>,
+) {
+    spatial_hash.clear();
+    for (entity, transform, collider) in query.iter() {
+        spatial_hash.insert_collider(entity, transform, collider);
+    }
+}
+
+fn insert_spawned(
+    trigger: Trigger<OnAdd, Collider>,
+    query: Query<(&Transform, &Collider)>,
+    mut spatial_hash: ResMut<SpatialHash>,
+) {
+    if let Ok((transform, collider)) = query.get(trigger.target()) {
+        spatial_hash.insert_collider(trigger.target(), transform, collider);
+    }
+}
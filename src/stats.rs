@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::StartRun;
+use crate::events::{
+    AsteroidDestroyed, GameOverReached, PlayerDied, SaucerDestroyed, Shooter, ShotFired,
+    WaveCleared,
+};
+
+// What the player did in the current run, counted from the gameplay events and reported when
+// it's over
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    pub shots_fired: u32,
+    pub asteroids_destroyed: u32,
+    pub saucers_destroyed: u32,
+    pub ships_lost: u32,
+    pub waves_cleared: u32,
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_observer(reset_stats)
+            .add_observer(count_shot)
+            .add_observer(count_asteroid)
+            .add_observer(count_saucer)
+            .add_observer(count_ship_lost)
+            .add_observer(count_wave)
+            .add_observer(report_stats);
+    }
+}
+
+fn reset_stats(_trigger: Trigger<StartRun>, mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn count_shot(trigger: Trigger<ShotFired>, mut stats: ResMut<RunStats>) {
+    if trigger.event().by == Shooter::Player {
+        stats.shots_fired += 1;
+    }
+}
+
+fn count_asteroid(trigger: Trigger<AsteroidDestroyed>, mut stats: ResMut<RunStats>) {
+    if trigger.event().by == Shooter::Player {
+        stats.asteroids_destroyed += 1;
+    }
+}
+
+fn count_saucer(_trigger: Trigger<SaucerDestroyed>, mut stats: ResMut<RunStats>) {
+    stats.saucers_destroyed += 1;
+}
+
+fn count_ship_lost(_trigger: Trigger<PlayerDied>, mut stats: ResMut<RunStats>) {
+    stats.ships_lost += 1;
+}
+
+fn count_wave(_trigger: Trigger<WaveCleared>, mut stats: ResMut<RunStats>) {
+    stats.waves_cleared += 1;
+}
+
+fn report_stats(_trigger: Trigger<GameOverReached>, stats: Res<RunStats>) {
+    info!(
+        "Run stats: shots fired {}, asteroids destroyed {}, saucers destroyed {}, \
+         ships lost {}, waves cleared {}",
+        stats.shots_fired,
+        stats.asteroids_destroyed,
+        stats.saucers_destroyed,
+        stats.ships_lost,
+        stats.waves_cleared
+    );
+}
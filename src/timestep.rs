@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use bevy::{
+    ecs::{component::HookContext, world::DeferredWorld},
+    prelude::*,
+};
+
+use crate::PlayField;
+
+// Default number of simulation ticks per second
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+
+// The simulation runs in `FixedUpdate` so it behaves the same at any frame rate. Entities with
+// this component are drawn in between their last two simulated positions, so the movement still
+// looks smooth when the frame rate doesn't match the tick rate
+#[derive(Component, Default)]
+#[component(on_add = init_interpolation)]
+pub struct InterpolatedTransform {
+    previous: Transform,
+    current: Transform,
+}
+
+// Start both ends of the interpolation at the spawn position, so new entities don't
+// slide in from the origin
+fn init_interpolation(mut world: DeferredWorld, context: HookContext) {
+    let Some(transform) = world.get::<Transform>(context.entity).copied() else {
+        return;
+    };
+    if let Some(mut interpolated) = world.get_mut::<InterpolatedTransform>(context.entity) {
+        interpolated.previous = transform;
+        interpolated.current = transform;
+    }
+}
+
+// Length of one simulation tick. Replays store it exactly, since rounding it differently
+// would change the outcome of a run
+pub struct TimestepPlugin {
+    pub timestep: Duration,
+}
+
+impl TimestepPlugin {
+    pub fn from_hz(tick_rate: f64) -> Self {
+        Self {
+            timestep: Duration::from_secs_f64(1.0 / tick_rate),
+        }
+    }
+}
+
+impl Default for TimestepPlugin {
+    fn default() -> Self {
+        Self::from_hz(DEFAULT_TICK_RATE)
+    }
+}
+
+impl Plugin for TimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_duration(self.timestep))
+            .add_systems(FixedFirst, restore_simulated_transforms)
+            .add_systems(FixedLast, store_simulated_transforms)
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            );
+    }
+}
+
+// Undo the interpolation before the simulation steps, so it continues from where it left off
+fn restore_simulated_transforms(mut query: Query<(&mut Transform, &mut InterpolatedTransform)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        *transform = interpolated.current;
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn store_simulated_transforms(mut query: Query<(&Transform, &mut InterpolatedTransform)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = *transform;
+    }
+}
+
+fn interpolate_transforms(
+    mut query: Query<(&mut Transform, &InterpolatedTransform)>,
+    fixed_time: Res<Time<Fixed>>,
+    play_field: Res<PlayField>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    // A jump this long can only be a wrap to the other side of the screen
+    let max_step = play_field.half_width().min(play_field.half_height());
+
+    for (mut transform, interpolated) in query.iter_mut() {
+        let InterpolatedTransform { previous, current } = interpolated;
+        if previous.translation.distance(current.translation) > max_step {
+            *transform = *current;
+            continue;
+        }
+        transform.translation = previous.translation.lerp(current.translation, alpha);
+        transform.rotation = previous.rotation.slerp(current.rotation, alpha);
+    }
+}
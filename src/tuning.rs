@@ -0,0 +1,916 @@
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::asteroid::{self, AsteroidSize};
+use crate::edges::{EdgeKind, EdgePolicy, GameMode};
+use crate::player::ReverseThrust;
+use crate::powerup;
+use crate::saucer::{self, SaucerSize};
+use crate::wave::{self, SpawnMode};
+use crate::{hyperspace, mechanics, player, scoring, weapon};
+
+// Gameplay values are read from this file in the assets folder. Edits are applied while the
+// game is running
+pub const TUNING_PATH: &str = "gameplay.tuning.ron";
+
+// Every gameplay value designers may want to change, grouped by what it affects. Any value left
+// out of the file keeps its default, which is the matching constant in the code
+#[derive(Asset, TypePath, Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameTuning {
+    pub mode: GameMode,
+    pub edges: EdgeTuning,
+    pub collision: CollisionTuning,
+    pub asteroid: AsteroidTuning,
+    pub bullet: BulletTuning,
+    pub player: PlayerTuning,
+    pub score: ScoreTuning,
+    pub wave: WaveTuning,
+    pub hyperspace: HyperspaceTuning,
+    pub saucer: SaucerTuning,
+    pub power_up: PowerUpTuning,
+    pub weapon: WeaponTuning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollisionTuning {
+    // The ship and saucers collide by the outlines of their sprites. Off, they collide by a
+    // circle, as they did before
+    pub outlines: bool,
+    // Things that wrap around the screen also collide across the edges, with what's at the
+    // opposite edge
+    pub across_edges: bool,
+}
+
+impl Default for CollisionTuning {
+    fn default() -> Self {
+        Self {
+            outlines: true,
+            across_edges: true,
+        }
+    }
+}
+
+// Edge policies that replace the ones of the game mode. `None` keeps the mode's
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeTuning {
+    pub ship: Option<EdgePolicy>,
+    pub asteroids: Option<EdgePolicy>,
+    pub bullets: Option<EdgePolicy>,
+    pub saucers: Option<EdgePolicy>,
+    pub power_ups: Option<EdgePolicy>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AsteroidTuning {
+    pub large_size: f32,
+    pub medium_size: f32,
+    pub small_size: f32,
+    pub large_speed: f32,
+    pub medium_speed: f32,
+    pub small_speed: f32,
+    // Number of large asteroids at the start of a run
+    pub initial_count: usize,
+    // Asteroids don't spawn closer than this to the center, where the player starts
+    pub min_spawn_distance: f32,
+    // Hits an asteroid takes before it breaks up
+    pub hit_points: u32,
+    // Waves, or the endless trickle from the edges
+    pub spawn_mode: SpawnMode,
+    // Seconds between two asteroids flying in from the edges, in the trickle mode
+    pub spawn_interval: f32,
+    // What a large and a medium asteroid break into, only ever smaller sizes. Small ones are
+    // gone when hit
+    pub large_fragments: Vec<AsteroidSize>,
+    pub medium_fragments: Vec<AsteroidSize>,
+    // Fragments keep the asteroid's velocity and fly apart from the line of the shot, see
+    // `asteroid::split_asteroid`. Off, they fly off in random directions, as they did before
+    pub inherit_momentum: bool,
+    // Speed along the shot a hit gives the fragments
+    pub impact_push: f32,
+    // Fastest an asteroid spins, in radians per second either way, 0 for none
+    pub max_spin: f32,
+    // How far the corners of a polygon can be pulled in, as a share of its radius
+    pub jaggedness: f32,
+}
+
+impl Default for AsteroidTuning {
+    fn default() -> Self {
+        Self {
+            large_size: asteroid::ASTEROID_LARGE_SIZE,
+            medium_size: asteroid::ASTEROID_MEDIUM_SIZE,
+            small_size: asteroid::ASTEROID_SMALL_SIZE,
+            large_speed: asteroid::ASTEROID_LARGE_SPEED,
+            medium_speed: asteroid::ASTEROID_MEDIUM_SPEED,
+            small_speed: asteroid::ASTEROID_SMALL_SPEED,
+            initial_count: asteroid::INITIAL_ASTEROIDS,
+            min_spawn_distance: asteroid::MIN_SPAWN_DISTANCE,
+            hit_points: 1,
+            spawn_mode: SpawnMode::default(),
+            spawn_interval: asteroid::ASTEROID_SPAWN_INTERVAL,
+            large_fragments: vec![AsteroidSize::Medium; 2],
+            medium_fragments: vec![AsteroidSize::Small; 2],
+            inherit_momentum: true,
+            impact_push: asteroid::ASTEROID_IMPACT_PUSH,
+            max_spin: asteroid::ASTEROID_MAX_SPIN,
+            jaggedness: asteroid::ASTEROID_JAGGEDNESS,
+        }
+    }
+}
+
+impl AsteroidTuning {
+    pub fn size(&self, size: AsteroidSize) -> f32 {
+        match size {
+            AsteroidSize::Large => self.large_size,
+            AsteroidSize::Medium => self.medium_size,
+            AsteroidSize::Small => self.small_size,
+        }
+    }
+
+    pub fn speed(&self, size: AsteroidSize) -> f32 {
+        match size {
+            AsteroidSize::Large => self.large_speed,
+            AsteroidSize::Medium => self.medium_speed,
+            AsteroidSize::Small => self.small_speed,
+        }
+    }
+
+    pub fn fragments(&self, size: AsteroidSize) -> &[AsteroidSize] {
+        match size {
+            AsteroidSize::Large => &self.large_fragments,
+            AsteroidSize::Medium => &self.medium_fragments,
+            AsteroidSize::Small => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulletTuning {
+    pub speed: f32,
+    // Seconds before a bullet disappears
+    pub lifetime: f32,
+}
+
+impl Default for BulletTuning {
+    fn default() -> Self {
+        Self {
+            speed: mechanics::BULLET_SPEED,
+            lifetime: mechanics::BULLET_LIFETIME,
+        }
+    }
+}
+
+// The ship's gun. Its bullets fly at `bullet.speed` for `bullet.lifetime` seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeaponTuning {
+    // Most shots per second, pressed or held. 0 to only fire on presses, as fast as they come
+    pub fire_rate: f32,
+    // Distance in front of the ship the bullets appear at
+    pub muzzle_offset: f32,
+    // Bullets per shot, fanned out `spread` radians apart
+    pub projectile_count: u32,
+    pub spread: f32,
+    // Hit points a bullet takes off what it hits
+    pub damage: u32,
+    pub projectile_size: f32,
+    // Most of the ship's bullets in flight at once, 0 for no limit. The arcade original had 4
+    pub max_bullets: usize,
+}
+
+impl Default for WeaponTuning {
+    fn default() -> Self {
+        Self {
+            fire_rate: weapon::WEAPON_FIRE_RATE,
+            muzzle_offset: weapon::WEAPON_MUZZLE_OFFSET,
+            projectile_count: weapon::WEAPON_PROJECTILE_COUNT,
+            spread: weapon::WEAPON_SPREAD,
+            damage: weapon::WEAPON_DAMAGE,
+            projectile_size: weapon::WEAPON_PROJECTILE_SIZE,
+            max_bullets: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub thrust_force: f32,
+    // Fraction of its speed the ship loses per second, 0 for none
+    pub drag: f32,
+    // Top speed of the ship, 0 for no limit
+    pub max_speed: f32,
+    // Radians per second
+    pub rotation_speed: f32,
+    // How quickly the ship gets up to `rotation_speed` and stops turning again, in radians per
+    // second squared. 0 turns at full speed right away
+    pub angular_acceleration: f32,
+    // What pulling back on the thrust does, and how hard
+    pub reverse_thrust: ReverseThrust,
+    pub reverse_force: f32,
+    // Extra drag while the ship isn't thrusting, with the inertial dampeners on
+    pub dampening: f32,
+    // Ships at the start of a run
+    pub lives: u32,
+    // Seconds between losing a ship and the next one appearing
+    pub respawn_delay: f32,
+    // Seconds a new ship can't be hit
+    pub invulnerability: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        Self {
+            thrust_force: player::PLAYER_THRUST_FORCE,
+            drag: player::PLAYER_DRAG,
+            max_speed: player::PLAYER_MAX_SPEED,
+            rotation_speed: player::PLAYER_ROTATION_SPEED,
+            angular_acceleration: player::PLAYER_ANGULAR_ACCELERATION,
+            reverse_thrust: ReverseThrust::default(),
+            reverse_force: player::PLAYER_REVERSE_FORCE,
+            dampening: player::PLAYER_DAMPENING,
+            lives: player::PLAYER_LIVES,
+            respawn_delay: player::PLAYER_RESPAWN_DELAY,
+            invulnerability: player::PLAYER_INVULNERABILITY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreTuning {
+    // Points for destroying an asteroid of each size
+    pub large_asteroid: u32,
+    pub medium_asteroid: u32,
+    pub small_asteroid: u32,
+    // An extra life every this many points, 0 for none
+    pub extra_life_every: u32,
+    // Extra lives at these scores, on top of `extra_life_every`
+    pub extra_life_at: Vec<u32>,
+    // Seconds after a kill for the next one to continue the combo
+    pub combo_window: f32,
+    // The multiplier goes up by one every this many kills in a combo
+    pub combo_kills_per_step: u32,
+    // 1 turns the combo multiplier off
+    pub combo_max_multiplier: u32,
+    // Points for shooting down a saucer of each size
+    pub large_saucer: u32,
+    pub small_saucer: u32,
+}
+
+impl Default for ScoreTuning {
+    fn default() -> Self {
+        Self {
+            large_asteroid: 20,
+            medium_asteroid: 50,
+            small_asteroid: 100,
+            extra_life_every: scoring::EXTRA_LIFE_EVERY,
+            extra_life_at: Vec::new(),
+            combo_window: scoring::COMBO_WINDOW,
+            combo_kills_per_step: scoring::COMBO_KILLS_PER_STEP,
+            combo_max_multiplier: scoring::COMBO_MAX_MULTIPLIER,
+            large_saucer: 200,
+            small_saucer: 1000,
+        }
+    }
+}
+
+impl ScoreTuning {
+    pub fn asteroid_points(&self, size: AsteroidSize) -> u32 {
+        match size {
+            AsteroidSize::Large => self.large_asteroid,
+            AsteroidSize::Medium => self.medium_asteroid,
+            AsteroidSize::Small => self.small_asteroid,
+        }
+    }
+
+    pub fn saucer_points(&self, size: SaucerSize) -> u32 {
+        match size {
+            SaucerSize::Large => self.large_saucer,
+            SaucerSize::Small => self.small_saucer,
+        }
+    }
+}
+
+// How waves grow, in the waves spawn mode. Wave 1 has `asteroid.initial_count` asteroids
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveTuning {
+    // Asteroids added to each wave after the first
+    pub extra_asteroids: usize,
+    // Asteroid speed added to each wave after the first, as a fraction of the base speed
+    pub speed_increase: f32,
+    // Waves stop growing at this many asteroids
+    pub max_asteroids: usize,
+    // Points for clearing a wave, times the wave number
+    pub clear_bonus: u32,
+    // Seconds between clearing a wave and the next one arriving
+    pub clear_delay: f32,
+}
+
+impl Default for WaveTuning {
+    fn default() -> Self {
+        Self {
+            extra_asteroids: wave::WAVE_EXTRA_ASTEROIDS,
+            speed_increase: wave::WAVE_SPEED_INCREASE,
+            max_asteroids: wave::WAVE_MAX_ASTEROIDS,
+            clear_bonus: wave::WAVE_CLEAR_BONUS,
+            clear_delay: wave::WAVE_CLEAR_DELAY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HyperspaceTuning {
+    // Seconds between two jumps
+    pub cooldown: f32,
+    // Chance from 0 to 1 that a jump destroys the ship
+    pub malfunction_chance: f32,
+    // Seconds the ship takes to vanish and appear again
+    pub jump_time: f32,
+    // Whether the ship comes out of hyperspace with the speed it went in, or standing still
+    pub keep_velocity: bool,
+}
+
+impl Default for HyperspaceTuning {
+    fn default() -> Self {
+        Self {
+            cooldown: hyperspace::HYPERSPACE_COOLDOWN,
+            malfunction_chance: hyperspace::HYPERSPACE_MALFUNCTION_CHANCE,
+            jump_time: hyperspace::HYPERSPACE_JUMP_TIME,
+            keep_velocity: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaucerTuning {
+    pub enabled: bool,
+    // Seconds between two saucers
+    pub spawn_interval: f32,
+    // Chance from 0 to 1 that a saucer is the small one
+    pub small_chance: f32,
+    pub large_size: f32,
+    pub small_size: f32,
+    pub large_speed: f32,
+    pub small_speed: f32,
+    // Seconds between two changes of course
+    pub turn_interval: f32,
+    // Seconds between two shots
+    pub fire_interval: f32,
+    pub bullet_speed: f32,
+    // Radians a shot can miss the ship by, pi fires in any direction
+    pub large_aim_error: f32,
+    pub small_aim_error: f32,
+    // Seconds before a saucer leaves the play field
+    pub stay_time: f32,
+    // Hits a saucer takes before it goes down
+    pub hit_points: u32,
+}
+
+impl Default for SaucerTuning {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            spawn_interval: saucer::SAUCER_SPAWN_INTERVAL,
+            small_chance: saucer::SAUCER_SMALL_CHANCE,
+            large_size: saucer::SAUCER_LARGE_SIZE,
+            small_size: saucer::SAUCER_SMALL_SIZE,
+            large_speed: saucer::SAUCER_LARGE_SPEED,
+            small_speed: saucer::SAUCER_SMALL_SPEED,
+            turn_interval: saucer::SAUCER_TURN_INTERVAL,
+            fire_interval: saucer::SAUCER_FIRE_INTERVAL,
+            bullet_speed: saucer::SAUCER_BULLET_SPEED,
+            large_aim_error: saucer::SAUCER_LARGE_AIM_ERROR,
+            small_aim_error: saucer::SAUCER_SMALL_AIM_ERROR,
+            stay_time: saucer::SAUCER_STAY_TIME,
+            hit_points: 1,
+        }
+    }
+}
+
+impl SaucerTuning {
+    pub fn size(&self, size: SaucerSize) -> f32 {
+        match size {
+            SaucerSize::Large => self.large_size,
+            SaucerSize::Small => self.small_size,
+        }
+    }
+
+    pub fn speed(&self, size: SaucerSize) -> f32 {
+        match size {
+            SaucerSize::Large => self.large_speed,
+            SaucerSize::Small => self.small_speed,
+        }
+    }
+
+    pub fn aim_error(&self, size: SaucerSize) -> f32 {
+        match size {
+            SaucerSize::Large => self.large_aim_error,
+            SaucerSize::Small => self.small_aim_error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerUpTuning {
+    // Chance from 0 to 1 that a shot down asteroid leaves a power-up
+    pub drop_chance: f32,
+    // Seconds a power-up waits to be collected
+    pub lifetime: f32,
+    pub speed: f32,
+    // Seconds a collected power-up lasts
+    pub duration: f32,
+    // Seconds between two shots while fire is held, with rapid fire
+    pub rapid_fire_interval: f32,
+    // Radians between the bullets of a triple shot
+    pub triple_shot_spread: f32,
+    // Asteroids a piercing bullet flies through before it stops at the next one
+    pub piercing_hits: u32,
+}
+
+impl Default for PowerUpTuning {
+    fn default() -> Self {
+        Self {
+            drop_chance: powerup::POWER_UP_DROP_CHANCE,
+            lifetime: powerup::POWER_UP_LIFETIME,
+            speed: powerup::POWER_UP_SPEED,
+            duration: powerup::POWER_UP_DURATION,
+            rapid_fire_interval: powerup::RAPID_FIRE_INTERVAL,
+            triple_shot_spread: powerup::TRIPLE_SHOT_SPREAD,
+            piercing_hits: powerup::PIERCING_HITS,
+        }
+    }
+}
+
+// A tuning value that would break the game, e.g. a zero bullet lifetime
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningError {
+    pub field: &'static str,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for TuningError {}
+
+impl GameTuning {
+    // What happens to `kind` at the edges of the play field in this run
+    pub fn edge_policy(&self, kind: EdgeKind) -> EdgePolicy {
+        let edges = &self.edges;
+        let policy = match kind {
+            EdgeKind::Ship => edges.ship,
+            EdgeKind::Asteroid => edges.asteroids,
+            EdgeKind::Bullet => edges.bullets,
+            EdgeKind::Saucer => edges.saucers,
+            EdgeKind::PowerUp => edges.power_ups,
+        };
+        policy.unwrap_or_else(|| self.mode.edge_policy(kind))
+    }
+
+    pub fn validate(&self) -> Result<(), TuningError> {
+        let positive = [
+            ("asteroid.large_size", self.asteroid.large_size),
+            ("asteroid.medium_size", self.asteroid.medium_size),
+            ("asteroid.small_size", self.asteroid.small_size),
+            ("asteroid.large_speed", self.asteroid.large_speed),
+            ("asteroid.medium_speed", self.asteroid.medium_speed),
+            ("asteroid.small_speed", self.asteroid.small_speed),
+            ("asteroid.spawn_interval", self.asteroid.spawn_interval),
+            ("bullet.speed", self.bullet.speed),
+            ("bullet.lifetime", self.bullet.lifetime),
+            ("player.thrust_force", self.player.thrust_force),
+            ("player.rotation_speed", self.player.rotation_speed),
+            ("player.respawn_delay", self.player.respawn_delay),
+            ("player.invulnerability", self.player.invulnerability),
+            ("wave.clear_delay", self.wave.clear_delay),
+            ("score.combo_window", self.score.combo_window),
+            ("hyperspace.cooldown", self.hyperspace.cooldown),
+            ("hyperspace.jump_time", self.hyperspace.jump_time),
+            ("saucer.spawn_interval", self.saucer.spawn_interval),
+            ("saucer.large_size", self.saucer.large_size),
+            ("saucer.small_size", self.saucer.small_size),
+            ("saucer.large_speed", self.saucer.large_speed),
+            ("saucer.small_speed", self.saucer.small_speed),
+            ("saucer.turn_interval", self.saucer.turn_interval),
+            ("saucer.fire_interval", self.saucer.fire_interval),
+            ("saucer.bullet_speed", self.saucer.bullet_speed),
+            ("saucer.stay_time", self.saucer.stay_time),
+            ("weapon.projectile_size", self.weapon.projectile_size),
+            ("power_up.lifetime", self.power_up.lifetime),
+            ("power_up.duration", self.power_up.duration),
+            (
+                "power_up.rapid_fire_interval",
+                self.power_up.rapid_fire_interval,
+            ),
+        ];
+        for (field, value) in positive {
+            // Written this way round so NaN is rejected too
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(TuningError {
+                    field,
+                    reason: "must be a finite number greater than zero",
+                });
+            }
+        }
+
+        if !(self.asteroid.min_spawn_distance >= 0.0
+            && self.asteroid.min_spawn_distance.is_finite())
+        {
+            return Err(TuningError {
+                field: "asteroid.min_spawn_distance",
+                reason: "must be a finite number, zero or more",
+            });
+        }
+        if !(self.wave.speed_increase >= 0.0 && self.wave.speed_increase.is_finite()) {
+            return Err(TuningError {
+                field: "wave.speed_increase",
+                reason: "must be a finite number, zero or more",
+            });
+        }
+        if !(0.0..=1.0).contains(&self.hyperspace.malfunction_chance) {
+            return Err(TuningError {
+                field: "hyperspace.malfunction_chance",
+                reason: "must be between 0 and 1",
+            });
+        }
+        if !(0.0..=1.0).contains(&self.power_up.drop_chance) {
+            return Err(TuningError {
+                field: "power_up.drop_chance",
+                reason: "must be between 0 and 1",
+            });
+        }
+        let non_negative = [
+            ("player.drag", self.player.drag),
+            ("player.max_speed", self.player.max_speed),
+            (
+                "player.angular_acceleration",
+                self.player.angular_acceleration,
+            ),
+            ("player.reverse_force", self.player.reverse_force),
+            ("player.dampening", self.player.dampening),
+            ("weapon.fire_rate", self.weapon.fire_rate),
+            ("weapon.muzzle_offset", self.weapon.muzzle_offset),
+            ("weapon.spread", self.weapon.spread),
+            ("asteroid.impact_push", self.asteroid.impact_push),
+            ("asteroid.max_spin", self.asteroid.max_spin),
+            ("power_up.speed", self.power_up.speed),
+            (
+                "power_up.triple_shot_spread",
+                self.power_up.triple_shot_spread,
+            ),
+        ];
+        for (field, value) in non_negative {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(TuningError {
+                    field,
+                    reason: "must be a finite number, zero or more",
+                });
+            }
+        }
+        if !(0.0..=1.0).contains(&self.saucer.small_chance) {
+            return Err(TuningError {
+                field: "saucer.small_chance",
+                reason: "must be between 0 and 1",
+            });
+        }
+        let aim_errors = [
+            ("saucer.large_aim_error", self.saucer.large_aim_error),
+            ("saucer.small_aim_error", self.saucer.small_aim_error),
+        ];
+        for (field, value) in aim_errors {
+            if !(0.0..=std::f32::consts::PI).contains(&value) {
+                return Err(TuningError {
+                    field,
+                    reason: "must be between 0 and pi",
+                });
+            }
+        }
+        if self.wave.max_asteroids > 100 {
+            return Err(TuningError {
+                field: "wave.max_asteroids",
+                reason: "must be at most 100",
+            });
+        }
+        let at_least_one = [
+            ("weapon.projectile_count", self.weapon.projectile_count),
+            ("weapon.damage", self.weapon.damage),
+            ("asteroid.hit_points", self.asteroid.hit_points),
+            ("saucer.hit_points", self.saucer.hit_points),
+        ];
+        for (field, value) in at_least_one {
+            if value == 0 {
+                return Err(TuningError {
+                    field,
+                    reason: "must be at least 1",
+                });
+            }
+        }
+        if !(0.0..0.9).contains(&self.asteroid.jaggedness) {
+            return Err(TuningError {
+                field: "asteroid.jaggedness",
+                reason: "must be at least 0 and below 0.9",
+            });
+        }
+        let fragments = [
+            (
+                "asteroid.large_fragments",
+                &self.asteroid.large_fragments,
+                AsteroidSize::Large,
+            ),
+            (
+                "asteroid.medium_fragments",
+                &self.asteroid.medium_fragments,
+                AsteroidSize::Medium,
+            ),
+        ];
+        for (field, fragments, parent) in fragments {
+            if fragments.contains(&parent) || fragments.contains(&AsteroidSize::Large) {
+                return Err(TuningError {
+                    field,
+                    reason: "must only list sizes smaller than the asteroid",
+                });
+            }
+            if fragments.len() > 10 {
+                return Err(TuningError {
+                    field,
+                    reason: "must list at most 10 fragments",
+                });
+            }
+        }
+        if self.weapon.projectile_count > 20 {
+            return Err(TuningError {
+                field: "weapon.projectile_count",
+                reason: "must be at most 20",
+            });
+        }
+        if self.score.combo_kills_per_step == 0 {
+            return Err(TuningError {
+                field: "score.combo_kills_per_step",
+                reason: "must be at least 1",
+            });
+        }
+        if self.score.combo_max_multiplier == 0 {
+            return Err(TuningError {
+                field: "score.combo_max_multiplier",
+                reason: "must be at least 1",
+            });
+        }
+        if !(1..=99).contains(&self.player.lives) {
+            return Err(TuningError {
+                field: "player.lives",
+                reason: "must be between 1 and 99",
+            });
+        }
+        if self.asteroid.initial_count > 100 {
+            return Err(TuningError {
+                field: "asteroid.initial_count",
+                reason: "must be at most 100",
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum TuningLoaderError {
+    Io(std::io::Error),
+    Ron(ron::de::SpannedError),
+    Invalid(TuningError),
+}
+
+impl fmt::Display for TuningLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TuningLoaderError::Io(error) => write!(f, "could not read tuning file: {error}"),
+            TuningLoaderError::Ron(error) => write!(f, "could not parse tuning file: {error}"),
+            TuningLoaderError::Invalid(error) => write!(f, "invalid tuning value: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TuningLoaderError {}
+
+// Loads `.tuning.ron` files. Files with invalid values fail to load, so a bad edit keeps the
+// previous values in play
+#[derive(Default)]
+struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    type Asset = GameTuning;
+    type Settings = ();
+    type Error = TuningLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<GameTuning, TuningLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(TuningLoaderError::Io)?;
+        let tuning: GameTuning = ron::de::from_bytes(&bytes).map_err(TuningLoaderError::Ron)?;
+        tuning.validate().map_err(TuningLoaderError::Invalid)?;
+        Ok(tuning)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+// The tuning file being watched, if the values come from a file
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<GameTuning>);
+
+// Whether the tuning is ready to start a run with. A missing or broken file is ready too, the
+// defaults are used then
+pub fn tuning_ready(asset_server: &AssetServer, handle: Option<&TuningHandle>) -> bool {
+    match handle {
+        Some(handle) => matches!(
+            asset_server.load_state(&handle.0),
+            LoadState::Loaded | LoadState::Failed(_)
+        ),
+        None => true,
+    }
+}
+
+// Keeps the `GameTuning` resource in sync with the tuning file. When `fixed` is set, e.g. when
+// playing back a replay, those values are used instead and the file is ignored
+pub struct TuningPlugin {
+    pub fixed: Option<GameTuning>,
+}
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameTuning>()
+            .init_asset_loader::<GameTuningLoader>();
+
+        match &self.fixed {
+            Some(tuning) => {
+                app.insert_resource(tuning.clone());
+            }
+            None => {
+                app.init_resource::<GameTuning>()
+                    .add_systems(Startup, load_tuning)
+                    .add_systems(Update, apply_tuning);
+            }
+        }
+    }
+}
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut events: EventReader<AssetEvent<GameTuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<GameTuning>>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && *id == handle.0.id()
+            && let Some(loaded) = assets.get(*id)
+        {
+            *tuning = loaded.clone();
+            info!("Applied gameplay tuning from {TUNING_PATH}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_of(tuning: &GameTuning) -> Option<&'static str> {
+        tuning.validate().err().map(|error| error.field)
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(GameTuning::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn shipped_file_is_valid() {
+        let file = include_str!("../assets/gameplay.tuning.ron");
+        let tuning: GameTuning = ron::from_str(file).expect("the shipped tuning file parses");
+        assert_eq!(tuning.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_values_that_must_be_positive() {
+        let mut tuning = GameTuning::default();
+        tuning.asteroid.large_speed = -10.0;
+        assert_eq!(field_of(&tuning), Some("asteroid.large_speed"));
+
+        let mut tuning = GameTuning::default();
+        tuning.bullet.lifetime = 0.0;
+        assert_eq!(field_of(&tuning), Some("bullet.lifetime"));
+
+        let mut tuning = GameTuning::default();
+        tuning.bullet.speed = f32::NAN;
+        assert_eq!(field_of(&tuning), Some("bullet.speed"));
+
+        let mut tuning = GameTuning::default();
+        tuning.player.thrust_force = f32::INFINITY;
+        assert_eq!(field_of(&tuning), Some("player.thrust_force"));
+    }
+
+    #[test]
+    fn rejects_negative_values() {
+        let mut tuning = GameTuning::default();
+        tuning.player.drag = -0.1;
+        assert_eq!(field_of(&tuning), Some("player.drag"));
+
+        let mut tuning = GameTuning::default();
+        tuning.weapon.spread = -1.0;
+        assert_eq!(field_of(&tuning), Some("weapon.spread"));
+    }
+
+    #[test]
+    fn rejects_chances_outside_zero_to_one() {
+        let mut tuning = GameTuning::default();
+        tuning.hyperspace.malfunction_chance = 1.5;
+        assert_eq!(field_of(&tuning), Some("hyperspace.malfunction_chance"));
+
+        let mut tuning = GameTuning::default();
+        tuning.power_up.drop_chance = -0.1;
+        assert_eq!(field_of(&tuning), Some("power_up.drop_chance"));
+    }
+
+    #[test]
+    fn rejects_jaggedness_of_point_nine_or_more() {
+        let mut tuning = GameTuning::default();
+        tuning.asteroid.jaggedness = 0.9;
+        assert_eq!(field_of(&tuning), Some("asteroid.jaggedness"));
+
+        tuning.asteroid.jaggedness = 0.89;
+        assert_eq!(tuning.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_fragments_not_smaller_than_the_asteroid() {
+        let mut tuning = GameTuning::default();
+        tuning.asteroid.medium_fragments = vec![AsteroidSize::Small, AsteroidSize::Medium];
+        assert_eq!(field_of(&tuning), Some("asteroid.medium_fragments"));
+
+        let mut tuning = GameTuning::default();
+        tuning.asteroid.large_fragments = vec![AsteroidSize::Small; 11];
+        assert_eq!(field_of(&tuning), Some("asteroid.large_fragments"));
+    }
+
+    #[test]
+    fn rejects_counts_out_of_range() {
+        let mut tuning = GameTuning::default();
+        tuning.weapon.projectile_count = 0;
+        assert_eq!(field_of(&tuning), Some("weapon.projectile_count"));
+
+        tuning.weapon.projectile_count = 21;
+        assert_eq!(field_of(&tuning), Some("weapon.projectile_count"));
+
+        let mut tuning = GameTuning::default();
+        tuning.weapon.damage = 0;
+        assert_eq!(field_of(&tuning), Some("weapon.damage"));
+
+        let mut tuning = GameTuning::default();
+        tuning.player.lives = 0;
+        assert_eq!(field_of(&tuning), Some("player.lives"));
+
+        let mut tuning = GameTuning::default();
+        tuning.wave.max_asteroids = 101;
+        assert_eq!(field_of(&tuning), Some("wave.max_asteroids"));
+    }
+
+    #[test]
+    fn error_names_the_field() {
+        let mut tuning = GameTuning::default();
+        tuning.score.combo_kills_per_step = 0;
+        let error = tuning.validate().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "score.combo_kills_per_step must be at least 1"
+        );
+    }
+}
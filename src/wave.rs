@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asteroid::{self, Asteroid, AsteroidLook, AsteroidTheme};
+use crate::events::WaveCleared;
+use crate::player::Player;
+use crate::rng::GameRng;
+use crate::tuning::GameTuning;
+use crate::{GameAssets, GameState, GameplaySet, PlayField};
+
+// Defaults, the values in play come from `GameTuning`
+pub const WAVE_EXTRA_ASTEROIDS: usize = 1;
+pub const WAVE_SPEED_INCREASE: f32 = 0.1;
+pub const WAVE_MAX_ASTEROIDS: usize = 11;
+pub const WAVE_CLEAR_BONUS: u32 = 500;
+pub const WAVE_CLEAR_DELAY: f32 = 3.0;
+
+// How asteroids keep coming during a run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpawnMode {
+    // A new, bigger and faster wave of asteroids once the field is cleared
+    #[default]
+    Waves,
+    // One wave at the start, then a large asteroid flies in from the edges every
+    // `asteroid.spawn_interval` seconds, without end
+    Trickle,
+}
+
+// The wave being played. Wave 1 starts with the run
+#[derive(Resource, Debug)]
+pub struct Wave {
+    pub number: u32,
+    // Runs between clearing a wave and the next one arriving
+    pub cleared: Option<Timer>,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            number: 1,
+            cleared: None,
+        }
+    }
+}
+
+// Spawn the large asteroids wave `number` starts with
+pub fn spawn_wave(
+    commands: &mut Commands,
+    number: u32,
+    play_field: &PlayField,
+    rng: &mut GameRng,
+    tuning: &GameTuning,
+    safe_spot: Vec3,
+    look: AsteroidLook,
+) {
+    let later_waves = number.saturating_sub(1);
+    let count = (tuning.asteroid.initial_count
+        + tuning.wave.extra_asteroids * later_waves as usize)
+        .min(tuning.wave.max_asteroids.max(tuning.asteroid.initial_count));
+    let speed_scale = 1.0 + tuning.wave.speed_increase * later_waves as f32;
+    asteroid::spawn_wave_asteroids(
+        commands,
+        play_field,
+        rng,
+        &tuning.asteroid,
+        count,
+        speed_scale,
+        safe_spot,
+        look,
+    );
+}
+
+// Bonus points for clearing wave `number`
+pub fn clear_bonus(tuning: &GameTuning, number: u32) -> u32 {
+    tuning.wave.clear_bonus.saturating_mul(number)
+}
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wave>().add_systems(
+            FixedUpdate,
+            advance_wave
+                .in_set(GameplaySet::Spawning)
+                .after(crate::spawn_asteroids_over_time),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn advance_wave(
+    mut commands: Commands,
+    mut wave: ResMut<Wave>,
+    asteroid_query: Query<(), With<Asteroid>>,
+    player_query: Query<&Transform, With<Player>>,
+    play_field: Res<PlayField>,
+    mut rng: ResMut<GameRng>,
+    tuning: Res<GameTuning>,
+    theme: Res<AsteroidTheme>,
+    assets: Res<GameAssets>,
+    time: Res<Time>,
+) {
+    if tuning.asteroid.spawn_mode != SpawnMode::Waves {
+        return;
+    }
+
+    let Some(timer) = wave.cleared.as_mut() else {
+        if asteroid_query.is_empty() {
+            let bonus = clear_bonus(&tuning, wave.number);
+            info!("Wave {} cleared, bonus {bonus}", wave.number);
+            commands.trigger(WaveCleared {
+                number: wave.number,
+                bonus,
+            });
+            wave.cleared = Some(Timer::from_seconds(
+                tuning.wave.clear_delay,
+                TimerMode::Once,
+            ));
+        }
+        return;
+    };
+
+    if timer.tick(time.delta()).finished() {
+        wave.cleared = None;
+        wave.number += 1;
+        // Keep the new asteroids away from the ship, or the middle while it respawns
+        let safe_spot = player_query
+            .single()
+            .map_or(Vec3::ZERO, |transform| transform.translation);
+        spawn_wave(
+            &mut commands,
+            wave.number,
+            &play_field,
+            &mut rng,
+            &tuning,
+            safe_spot,
+            AsteroidLook {
+                theme: *theme,
+                sprite: &assets.asteroid,
+            },
+        );
+    }
+}
+
+// Tag component for the text announcing a cleared wave
+#[derive(Component)]
+struct WaveBanner;
+
+// Shows "Wave N cleared" in the middle of the screen between two waves
+pub fn wave_banner_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Loading), spawn_wave_banner)
+        .add_observer(show_wave_banner)
+        .add_systems(Update, hide_wave_banner);
+}
+
+fn spawn_wave_banner(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        crate::OnGameScreen,
+        children![(
+            Text::new(""),
+            TextFont {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new(JustifyText::Center, LineBreak::NoWrap),
+            WaveBanner,
+        )],
+    ));
+}
+
+fn show_wave_banner(trigger: Trigger<WaveCleared>, mut query: Query<&mut Text, With<WaveBanner>>) {
+    let event = trigger.event();
+    for mut text in query.iter_mut() {
+        text.0 = format!("Wave {} cleared!\nBonus: {}", event.number, event.bonus);
+    }
+}
+
+// Until the next wave comes, and out of the way of the Game Over and pause screens
+fn hide_wave_banner(
+    wave: Res<Wave>,
+    game_state: Res<State<GameState>>,
+    mut query: Query<&mut Visibility, With<WaveBanner>>,
+) {
+    if !(wave.is_changed() || game_state.is_changed()) {
+        return;
+    }
+    let shown = wave.cleared.is_some() && *game_state.get() == GameState::Playing;
+    for mut visibility in query.iter_mut() {
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::GameplaySet;
+use crate::collider::Collider;
+use crate::events::{Shooter, ShotFired};
+use crate::hyperspace::HyperspaceJump;
+use crate::input::PlayerInput;
+use crate::mechanics::{Bullet, BulletLifetime, BulletVelocity, Damage};
+use crate::player::Player;
+use crate::powerup::{ActivePowerUps, Piercing, PowerUpKind};
+use crate::saucer::SaucerBullet;
+use crate::timestep::InterpolatedTransform;
+use crate::tuning::GameTuning;
+
+// Defaults, the values in play come from `GameTuning`
+pub const WEAPON_FIRE_RATE: f32 = 4.0;
+pub const WEAPON_MUZZLE_OFFSET: f32 = 20.0;
+pub const WEAPON_PROJECTILE_COUNT: u32 = 1;
+pub const WEAPON_SPREAD: f32 = 0.15;
+pub const WEAPON_DAMAGE: u32 = 1;
+pub const WEAPON_PROJECTILE_SIZE: f32 = 10.0;
+
+// The ship's gun. Pressing fire shoots as soon as the gun is ready again, and holding it down
+// keeps shooting, neither faster than `fire_rate`. The stats come from the `weapon` and
+// `bullet` sections of the gameplay tuning and follow its changes. The sprite is kept, so it
+// can be swapped for an image
+#[derive(Component, Debug, Clone)]
+pub struct Weapon {
+    // Most shots per second, pressed or held. 0 to only fire on presses, as fast as they come
+    pub fire_rate: f32,
+    // Distance in front of the ship the bullets appear at
+    pub muzzle_offset: f32,
+    // Bullets per shot, fanned out `spread` radians apart
+    pub projectile_count: u32,
+    pub spread: f32,
+    pub projectile_speed: f32,
+    // Seconds before a bullet disappears
+    pub lifetime: f32,
+    // Hit points a bullet takes off what it hits
+    pub damage: u32,
+    pub sprite: Sprite,
+    pub collider: Collider,
+    // Most of the ship's bullets in flight at once, 0 for no limit. A shot that doesn't fit
+    // fires the bullets that do, those closest to straight ahead first
+    pub max_bullets: usize,
+    // Time since the last shot
+    since_shot: Duration,
+}
+
+impl Weapon {
+    pub fn new(tuning: &GameTuning) -> Self {
+        let mut weapon = Self {
+            fire_rate: 0.0,
+            muzzle_offset: 0.0,
+            projectile_count: 0,
+            spread: 0.0,
+            projectile_speed: 0.0,
+            lifetime: 0.0,
+            damage: 0,
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.5, 0.0),
+                ..default()
+            },
+            collider: Collider::circle(0.0),
+            max_bullets: 0,
+            since_shot: Duration::MAX,
+        };
+        weapon.apply_tuning(tuning);
+        weapon
+    }
+
+    // Take the stats from the tuning, keeping the sprite image and the time since the last shot
+    pub fn apply_tuning(&mut self, tuning: &GameTuning) {
+        let weapon = &tuning.weapon;
+        self.fire_rate = weapon.fire_rate;
+        self.muzzle_offset = weapon.muzzle_offset;
+        self.projectile_count = weapon.projectile_count;
+        self.spread = weapon.spread;
+        self.projectile_speed = tuning.bullet.speed;
+        self.lifetime = tuning.bullet.lifetime;
+        self.damage = weapon.damage;
+        self.sprite.custom_size = Some(Vec2::splat(weapon.projectile_size));
+        self.collider = Collider::circle(weapon.projectile_size / 2.0);
+        self.max_bullets = weapon.max_bullets;
+    }
+
+    // Whether the weapon fires this tick. Rapid fire shoots every `rapid_fire` seconds if
+    // that's faster than the fire rate
+    fn trigger(&mut self, input: &PlayerInput, rapid_fire: Option<f32>, delta: Duration) -> bool {
+        self.since_shot = self.since_shot.saturating_add(delta);
+        let fire_interval = (self.fire_rate > 0.0).then(|| 1.0 / self.fire_rate);
+        let interval = match (fire_interval, rapid_fire) {
+            (Some(fire_interval), Some(rapid_fire)) => Some(fire_interval.min(rapid_fire)),
+            (interval, None) | (None, interval) => interval,
+        }
+        .map(Duration::from_secs_f32);
+
+        let ready = interval.is_none_or(|interval| self.since_shot >= interval);
+        let held = input.fire_held && interval.is_some();
+        if !ready || !(input.fire || held) {
+            return false;
+        }
+
+        // While fire is held, carry over how late this shot is, so the shots keep to the rate
+        // however it lines up with the ticks
+        self.since_shot = match interval {
+            Some(interval) if !input.fire && self.since_shot - interval < interval => {
+                self.since_shot - interval
+            }
+            _ => Duration::ZERO,
+        };
+        true
+    }
+
+    // Directions of the bullets of one shot, relative to the ship
+    fn angles(&self) -> impl Iterator<Item = f32> + '_ {
+        let middle = (self.projectile_count.max(1) - 1) as f32 / 2.0;
+        (0..self.projectile_count).map(move |index| (index as f32 - middle) * self.spread)
+    }
+}
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, fire_weapon.in_set(GameplaySet::Spawning))
+            .add_systems(
+                Update,
+                apply_weapon_tuning.run_if(resource_changed::<GameTuning>),
+            );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn fire_weapon(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut player_query: Query<
+        (&Transform, &mut Weapon, &ActivePowerUps),
+        (With<Player>, Without<HyperspaceJump>),
+    >,
+    bullet_query: Query<(), (With<Bullet>, Without<SaucerBullet>)>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    let Ok((player_transform, mut weapon, power_ups)) = player_query.single_mut() else {
+        return;
+    };
+    let rapid_fire = power_ups
+        .has(PowerUpKind::RapidFire)
+        .then_some(tuning.power_up.rapid_fire_interval);
+    if !weapon.trigger(&input, rapid_fire, time.delta()) {
+        return;
+    }
+    let room = match weapon.max_bullets {
+        0 => usize::MAX,
+        max_bullets => max_bullets.saturating_sub(bullet_query.iter().count()),
+    };
+    if room == 0 {
+        return;
+    }
+
+    // Triple shot turns every bullet into three
+    let spread = tuning.power_up.triple_shot_spread;
+    let extra_angles: &[f32] = if power_ups.has(PowerUpKind::TripleShot) {
+        &[-spread, 0.0, spread]
+    } else {
+        &[0.0]
+    };
+    let mut angles: Vec<f32> = weapon
+        .angles()
+        .flat_map(|angle| extra_angles.iter().map(move |extra| angle + extra))
+        .collect();
+    if angles.len() > room {
+        angles.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+        angles.truncate(room);
+    }
+
+    for angle in angles {
+        let direction = player_transform.rotation * Quat::from_rotation_z(angle) * Vec3::Y;
+        let position = player_transform.translation + direction * weapon.muzzle_offset;
+
+        let mut bullet = commands.spawn((
+            weapon.sprite.clone(),
+            Transform::from_translation(position),
+            GlobalTransform::default(),
+            InterpolatedTransform::default(),
+            Bullet,
+            BulletVelocity(direction.truncate() * weapon.projectile_speed),
+            BulletLifetime(Timer::from_seconds(weapon.lifetime, TimerMode::Once)),
+            Damage(weapon.damage),
+            weapon.collider.clone(),
+        ));
+        if power_ups.has(PowerUpKind::Piercing) {
+            bullet.insert(Piercing(tuning.power_up.piercing_hits));
+        }
+    }
+    commands.trigger(ShotFired {
+        position: player_transform.translation,
+        direction: (player_transform.rotation * Vec3::Y).truncate(),
+        by: Shooter::Player,
+    });
+}
+
+fn apply_weapon_tuning(mut query: Query<&mut Weapon>, tuning: Res<GameTuning>) {
+    for mut weapon in query.iter_mut() {
+        weapon.apply_tuning(&tuning);
+    }
+}